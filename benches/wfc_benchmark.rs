@@ -117,5 +117,32 @@ pub fn multi_dimension(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, simple, multi_dimension);
+pub fn reused(c: &mut Criterion) {
+    let tiles = vec![Water, Sand, Forest];
+    let possible_neighbours = PossibleNeighbours::new(
+        [
+            (Water, Water),
+            (Water, Sand),
+            (Sand, Water),
+            (Sand, Sand),
+            (Sand, Forest),
+            (Forest, Forest),
+        ],
+        &tiles,
+    );
+
+    let mut wfc = WfcBuilder::<Tile, ConstCell<3>>::new(50, 50, tiles)
+        .with_constraint(possible_neighbours)
+        .build()
+        .unwrap();
+
+    c.bench_function("reused", |b| {
+        b.iter(|| {
+            wfc.reset(42);
+            wfc.collapse_in_place().unwrap();
+        })
+    });
+}
+
+criterion_group!(benches, simple, multi_dimension, reused);
 criterion_main!(benches);
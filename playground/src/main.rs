@@ -1,5 +1,5 @@
 use crossterm::event::{Event, KeyCode, KeyEvent, read};
-use pad::position::Position;
+use pad::{p, position::Position};
 use ratatui::{
     DefaultTerminal,
     layout::{Constraint, Flex, Layout},
@@ -10,11 +10,10 @@ use ratatui::{
     widgets::{Block, Cell, Clear, Row, StatefulWidget, Table, TableState, Widget},
 };
 use ratatui_tools::tilemap::{Char, TileMap};
-use wave_function_collapse::{WfcBuilder, cell::DynCell, constraints::PossibleNeighbours};
+use wave_function_collapse::{Wfc, WfcBuilder, cell::DynCell, constraints::PossibleNeighbours};
 
 // TODO Some ideas to further improve the playground and even make it a standalone application:
 // - Add a seed input (requires a text input widget)
-// - Allow step-by-step collapsing
 // - Add a cursor to the map to collapse specific positions
 // - Add scrollbars to the map to allow bigger boards
 // - Load tiles from files
@@ -49,6 +48,8 @@ enum Message {
     DecrementWeight(usize),
     /// Perform a wave function collapse with the current settings
     Collapse,
+    /// Perform a single step of the wave function collapse
+    Step,
 }
 
 struct State {
@@ -66,6 +67,18 @@ struct State {
     seed: String,
     /// All currently collapsed positions
     collapsed_positions: Vec<(Position, Tile)>,
+    /// The Wfc which is currently collapsed step by step, if any
+    stepped_wfc: Option<SteppedWfc>,
+    /// The error of the last collapse or step, if it failed
+    error: Option<String>,
+}
+
+/// A [Wfc] which is collapsed step by step, together with the size of its board. The size
+/// settings might change while stepping, so they cannot be used to read the board.
+struct SteppedWfc {
+    wfc: Wfc<Tile, DynCell>,
+    width: usize,
+    height: usize,
 }
 
 impl Default for State {
@@ -78,6 +91,8 @@ impl Default for State {
             height: State::MIN_WIDTH_HEIGHT,
             seed: "42".into(),
             collapsed_positions: vec![],
+            stepped_wfc: None,
+            error: None,
         }
     }
 }
@@ -177,15 +192,35 @@ impl App {
                 match self.handle_events(key) {
                     Message::None => {}
                     Message::CloseApp => self.state.should_exit = true,
-                    Message::IncrementWidth => self.state.increment_width(),
-                    Message::DecrementWidth => self.state.decrement_widht(),
-                    Message::IncrementHeight => self.state.increment_height(),
-                    Message::DecrementHeight => self.state.decrement_height(),
+                    // a change of the settings starts a new stepped Wfc with the next step
+                    Message::IncrementWidth => {
+                        self.state.stepped_wfc = None;
+                        self.state.increment_width()
+                    }
+                    Message::DecrementWidth => {
+                        self.state.stepped_wfc = None;
+                        self.state.decrement_widht()
+                    }
+                    Message::IncrementHeight => {
+                        self.state.stepped_wfc = None;
+                        self.state.increment_height()
+                    }
+                    Message::DecrementHeight => {
+                        self.state.stepped_wfc = None;
+                        self.state.decrement_height()
+                    }
                     Message::OpenWeightDialog => self.weight_dialog = Some(WeightDialog::default()),
                     Message::CloseWeightDialog => self.weight_dialog = None,
-                    Message::IncrementWeight(index) => self.state.increment_weight(index),
-                    Message::DecrementWeight(index) => self.state.decrement_weight(index),
-                    Message::Collapse => self.state.collapsed_positions = self.collapse(),
+                    Message::IncrementWeight(index) => {
+                        self.state.stepped_wfc = None;
+                        self.state.increment_weight(index)
+                    }
+                    Message::DecrementWeight(index) => {
+                        self.state.stepped_wfc = None;
+                        self.state.decrement_weight(index)
+                    }
+                    Message::Collapse => self.collapse(),
+                    Message::Step => self.step(),
                 }
             }
         }
@@ -214,11 +249,67 @@ impl App {
             }
             KeyCode::Esc => Message::CloseApp,
             KeyCode::Char('c') => Message::Collapse,
+            KeyCode::Char('s') => Message::Step,
             _ => self.settings_panel.handle_events(event),
         }
     }
 
-    fn collapse(&self) -> Vec<(Position, Tile)> {
+    /// Perform a full wave function collapse with the current settings.
+    fn collapse(&mut self) {
+        self.state.stepped_wfc = None;
+
+        match self.create_wfc().collapse() {
+            Ok(grid) => {
+                self.state.collapsed_positions = grid.into_vec();
+                self.state.error = None;
+            }
+            Err(e) => self.state.error = Some(e.to_string()),
+        }
+    }
+
+    /// Perform a single step of the current stepped Wfc. A new one is created if
+    /// no Wfc is currently stepped.
+    fn step(&mut self) {
+        if self.state.stepped_wfc.is_none() {
+            self.state.stepped_wfc = Some(SteppedWfc {
+                wfc: self.create_wfc(),
+                width: self.state.width,
+                height: self.state.height,
+            });
+            self.state.error = None;
+        }
+
+        let stepped = self
+            .state
+            .stepped_wfc
+            .as_mut()
+            .expect("The stepped Wfc should exist");
+
+        match stepped.wfc.step() {
+            Ok(Some(_)) => {
+                let wfc = &stepped.wfc;
+
+                self.state.collapsed_positions = p!(0, 0)
+                    .iter_to(p!(stepped.width - 1, stepped.height - 1))
+                    .filter_map(|pos| {
+                        let mut tiles = wfc.get_possible_tiles(pos);
+
+                        match (tiles.next(), tiles.next()) {
+                            (Some(tile), None) => Some((pos, *tile)),
+                            _ => None,
+                        }
+                    })
+                    .collect()
+            }
+            Ok(None) => self.state.stepped_wfc = None,
+            Err(e) => {
+                self.state.error = Some(e.to_string());
+                self.state.stepped_wfc = None
+            }
+        }
+    }
+
+    fn create_wfc(&self) -> Wfc<Tile, DynCell> {
         use Tile::*;
 
         let tiles = self.state.tiles.clone();
@@ -241,8 +332,6 @@ impl App {
             .with_seed(42)
             .build()
             .unwrap()
-            .collapse()
-            .unwrap()
     }
 }
 
@@ -313,11 +402,19 @@ impl StatefulWidget for &WfcMap {
             }
         });
 
-        let block = Block::bordered()
+        let mut block = Block::bordered()
             .title_top(" Wfc Map <1> ")
-            .title_bottom(" Collapse <c> ")
+            .title_bottom(" Collapse <c> Step <s> ")
             .border_style(style);
 
+        if let Some(error) = &state.error {
+            block = block.title_bottom(
+                Line::from(format!(" {error} "))
+                    .right_aligned()
+                    .style(Style::new().fg(Color::Red)),
+            );
+        }
+
         let inner = block.inner(area);
 
         block.render(area, buf);
@@ -0,0 +1,123 @@
+use crate::{
+    WfcError,
+    board::{Board, BoardSnapshot},
+    cell::Cell,
+    constraints::TileConstraints,
+    observer::Observers,
+    topology::Topology,
+};
+use std::collections::VecDeque;
+
+/// Settings for the backtracking of a [crate::Wfc].
+///
+/// With backtracking enabled, the state of the board is remembered before every decision
+/// (the collapse of a cell to a random tile). If a decision leads to a contradiction, the board
+/// is reset to the state before the decision, the chosen tile is banned from the cell and the
+/// wfc continues with the remaining tiles.
+#[derive(Clone, Copy, Debug)]
+pub struct Backtracking {
+    /// The maximum amount of decisions which are remembered. If more decisions are made,
+    /// the oldest ones are forgotten and cannot be undone anymore.
+    pub max_depth: usize,
+    /// The maximum amount of times a decision can be undone during one collapse.
+    pub max_backtracks: usize,
+}
+
+/// A decision made by the wfc, alongside the state of the board before it was made.
+struct Decision<C: Cell> {
+    snapshot: BoardSnapshot<C>,
+    cell: usize,
+    index: u16,
+}
+
+/// Remembers the decisions of the wfc and undoes them if necessary.
+pub(crate) struct Backtracker<C: Cell> {
+    settings: Backtracking,
+    decisions: VecDeque<Decision<C>>,
+    num_backtracks: usize,
+}
+
+impl<C: Cell> Backtracker<C> {
+    pub(crate) fn new(settings: Backtracking) -> Self {
+        Backtracker {
+            settings,
+            decisions: VecDeque::new(),
+            num_backtracks: 0,
+        }
+    }
+
+    /// Forget all decisions and reset the amount of backtracks.
+    pub(crate) fn reset(&mut self) {
+        self.decisions.clear();
+        self.num_backtracks = 0;
+    }
+
+    /// Remember the current state of the board before the given tile index is chosen
+    /// in the given cell.
+    pub(crate) fn record<G: Topology>(
+        &mut self,
+        board: &Board<C, G>,
+        cell: usize,
+        index: u16,
+    ) {
+        if self.settings.max_depth == 0 {
+            return;
+        }
+
+        let snapshot = if self.decisions.len() >= self.settings.max_depth {
+            // reuse the allocations of the oldest decision, as it gets forgotten anyway
+            let mut oldest = self
+                .decisions
+                .pop_front()
+                .expect("At least one decision should exist");
+            board.snapshot_into(&mut oldest.snapshot);
+            oldest.snapshot
+        } else {
+            board.snapshot()
+        };
+
+        self.decisions.push_back(Decision {
+            snapshot,
+            cell,
+            index,
+        });
+    }
+
+    /// Undo decisions until the board is in a state without contradiction again.
+    ///
+    /// The latest decision gets undone and its tile is removed from the possible tiles of its cell.
+    /// If this leads to another contradiction, the decision before it gets undone, and so on.
+    /// Returns the given error if no more decisions can be undone or the maximum amount of
+    /// backtracks is reached.
+    pub(crate) fn backtrack<T, G: Topology>(
+        &mut self,
+        board: &mut Board<C, G>,
+        tile_constraints: &mut TileConstraints<T, G>,
+        tiles: &[T],
+        observers: &mut Observers<T, G::Position>,
+        mut error: WfcError<G::Position>,
+    ) -> Result<(), WfcError<G::Position>> {
+        loop {
+            if self.num_backtracks >= self.settings.max_backtracks {
+                return Err(error);
+            }
+
+            let Some(decision) = self.decisions.pop_back() else {
+                return Err(error);
+            };
+
+            self.num_backtracks += 1;
+            board.restore(&decision.snapshot);
+
+            if let Err(e) = board.remove_index(decision.cell, decision.index, observers) {
+                error = e;
+                continue;
+            }
+
+            match board.propagate(decision.cell, tile_constraints, tiles, observers) {
+                Ok(_) => return Ok(()),
+                Err(e) => error = e,
+            }
+        }
+    }
+}
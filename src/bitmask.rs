@@ -0,0 +1,113 @@
+use crate::cell::PossibleIndices;
+
+/// A table of bitmasks of tiles, where bit i of a mask tells if tile i is part of it.
+///
+/// The constraints store the allowed neighbours of every tile like this. A mask only needs one bit per tile,
+/// so even thousands of tiles stay small, and a neighbour can be checked with a bitwise AND if its possible
+/// indices are bits as well.
+#[derive(Clone, Debug)]
+pub(crate) struct TileMasks {
+    /// The amount of [u128] words of a single mask
+    words_per_mask: usize,
+    /// The words of all masks, one mask after the other
+    words: Vec<u128>,
+}
+
+impl TileMasks {
+    /// Create the given amount of empty masks for the given amount of tiles.
+    pub fn new(
+        num_masks: usize,
+        num_tiles: usize,
+    ) -> Self {
+        let words_per_mask = num_tiles.div_ceil(u128::BITS as usize);
+
+        TileMasks {
+            words_per_mask,
+            words: vec![0; num_masks * words_per_mask],
+        }
+    }
+
+    /// Return the words of the given mask.
+    pub fn mask(
+        &self,
+        mask: usize,
+    ) -> &[u128] {
+        &self.words[(mask * self.words_per_mask)..((mask + 1) * self.words_per_mask)]
+    }
+
+    /// Replace the given mask with the given words, which must be a mask for the same amount of tiles.
+    pub fn set_mask(
+        &mut self,
+        mask: usize,
+        words: &[u128],
+    ) {
+        self.words[(mask * self.words_per_mask)..((mask + 1) * self.words_per_mask)]
+            .copy_from_slice(words);
+    }
+
+    /// Add the given tile to the given mask.
+    pub fn insert(
+        &mut self,
+        mask: usize,
+        tile: u16,
+    ) {
+        let word = self.word_index(mask, tile);
+        self.words[word] |= Self::bit(tile);
+    }
+
+    /// Tells if the given tile is part of the given mask.
+    pub fn contains(
+        &self,
+        mask: usize,
+        tile: u16,
+    ) -> bool {
+        self.words[self.word_index(mask, tile)] & Self::bit(tile) != 0
+    }
+
+    /// Tells if any of the given tiles is part of the given mask.
+    pub fn contains_any(
+        &self,
+        mask: usize,
+        tiles: PossibleIndices,
+    ) -> bool {
+        match tiles {
+            // all tiles can be checked at once. They fit into the first word of the mask,
+            // as the bitarray has at most 128 tiles, and the bit of tile i is bit i in both
+            PossibleIndices::BitArray { array, .. } => {
+                self.words[mask * self.words_per_mask] & array.value() != 0
+            }
+            PossibleIndices::Array { .. } => tiles.iter().any(|tile| self.contains(mask, tile)),
+        }
+    }
+
+    /// Return the index of the word of the given mask which holds the bit of the given tile.
+    fn word_index(
+        &self,
+        mask: usize,
+        tile: u16,
+    ) -> usize {
+        mask * self.words_per_mask + tile as usize / u128::BITS as usize
+    }
+
+    /// Return the bit of the given tile in its word of a mask.
+    fn bit(tile: u16) -> u128 {
+        1 << (tile as u32 % u128::BITS)
+    }
+}
+
+/// Return the tiles of the given words of a mask in ascending order.
+pub(crate) fn tiles_in(words: &[u128]) -> impl Iterator<Item = u16> + '_ {
+    words.iter().enumerate().flat_map(|(i, word)| {
+        let mut word = *word;
+
+        std::iter::from_fn(move || {
+            if word == 0 {
+                return None;
+            }
+
+            let bit = word.trailing_zeros();
+            word &= word - 1;
+            Some((i as u32 * u128::BITS + bit) as u16)
+        })
+    })
+}
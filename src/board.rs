@@ -1,106 +1,373 @@
-use crate::{cell::Cell, WfcError};
+use crate::{cell::Cell, heuristic::EntropyHeuristic, random::Random, WfcError};
+use crate::entropy::{CellEntropy, EntropyQueue};
 use crate::constraints::TileConstraints;
-use pad::p;
-use pad::position::Position;
+use crate::direction::NeighbourDirection;
+use crate::observer::Observers;
+use crate::propagator::AdjacencyPropagator;
+use crate::topology::{SquareGrid, Topology};
 use std::collections::{BTreeSet, VecDeque};
-// todo Maybe try using a set of not collapsed positions
+use std::ops::Range;
 
 /// Contains the current state of the WFC with all the cells at their respective positions.
 /// The WFC is done if all positions on the board are collapsed.
-pub struct Board<C: Cell> {
-    /// Width of the [Board]
-    width: usize,
-    /// Height of the [Board]
-    height: usize,
+///
+/// The cells are stored by their index in the [Topology], and positions are only used to talk to the outside.
+pub struct Board<C: Cell, G: Topology> {
+    /// The shape of the [Board]
+    topology: G,
+    /// The neighbours of every cell and the direction they lie in, see [Board::neighbour_range]
+    neighbours: Vec<(G::Direction, usize)>,
+    /// For every cell, where its neighbours start in `neighbours`, with an additional entry for the end
+    neighbour_starts: Vec<usize>,
     /// The [Cell]s of the [Board], which tell what tiles are still possible
     cells: Vec<C>,
-    pub (crate) weights: Vec<f32>,
-    /// All [Position]s which are not collapsed yet. Used to more efficiently find the
+    /// The amount of tiles which are possible in a fresh cell
+    num_tiles: usize,
+    /// The weight of every tile, which is the same in every cell unless a weight map is used
+    tile_weights: Vec<f32>,
+    /// The base weights of the tiles in every cell, if a weight map makes them different from the `tile_weights`.
+    /// The weights of a cell are stored at `cell_index * num_tiles..(cell_index + 1) * num_tiles`. Empty without
+    /// a weight map.
+    base_weights: Vec<f32>,
+    /// The current weights of the tiles in every cell, which start with the base weights and get
+    /// changed by the weight modifiers of the constraints. Uses the same layout as `base_weights`.
+    /// Empty until a weight modifier changes a weight, so boards without modifiers don't need it.
+    cell_weights: Vec<f32>,
+    /// The cached Shannon entropy of every cell, which is updated whenever its weights change
+    entropies: Vec<CellEntropy>,
+    /// A random number for every cell, which decides between cells with the same entropy.
+    /// Otherwise, the cell nearest to the upper left corner would always be chosen.
+    tie_breakers: Vec<u32>,
+    /// The not collapsed cells ordered by their entropy, if the wfc selects cells by an [EntropyHeuristic]
+    entropy_queue: Option<EntropyQueue>,
+    /// For every tile in every cell and every direction, how many possible tiles of the neighbour in this
+    /// direction allow the tile. Only used with an [AdjacencyPropagator], otherwise it is empty. The supports of
+    /// a cell are stored like `initial_supports`, starting at `cell_index * initial_supports.len()`.
+    supports: Vec<u16>,
+    /// The supports of every tile and direction in a fresh cell, see [AdjacencyPropagator::index] for the layout
+    initial_supports: Vec<u16>,
+    /// The preallocated stack of tiles which were removed from a cell, but whose removal was not propagated
+    /// yet. Only used with an [AdjacencyPropagator].
+    removed_tiles: Vec<(usize, u16)>,
+    /// All cells which are not collapsed yet. Used to more efficiently find the
     /// next cell with the lowest entropy.
-    non_collapsed_positions: BTreeSet<Position>,
-    /// The preallocated queue which will be used to hold positions to propagate next
+    non_collapsed_cells: BTreeSet<usize>,
+    /// Tells for every cell if it was collapsed directly, by the wfc choosing its tile or by
+    /// [crate::Wfc::collapse_tiles]. The propagation never changes these cells. Cells which got collapsed
+    /// by the propagation are still checked, as a later change of a neighbour can make their tile impossible.
+    decided_cells: Vec<bool>,
+    /// The preallocated queue which will be used to hold cells to propagate next
     /// in the propagation step.
-    propagation_queue: VecDeque<Position>,
+    propagation_queue: VecDeque<usize>,
+    /// All cells whose possible tiles changed since the last call to [Board::clear_changed_positions].
+    changed_cells: Vec<usize>,
 }
 
-impl<C: Cell> Board<C> {
+impl<C: Cell, G: Topology> Board<C, G> {
+    /// Create a new board with the given weight for every tile. If a weight map is given, the weight
+    /// of a tile in a cell is multiplied with the weight returned by the map for its position.
     pub fn new(
-        width: usize,
-        height: usize,
+        topology: G,
         num_tiles: usize,
-        weights: &[f32],
+        tile_weights: Vec<f32>,
+        weight_map: Option<&dyn Fn(G::Position, usize) -> f32>,
     ) -> Self {
-        let cells = (0..(width * height))
-            .map(|_| C::new(num_tiles))
-            .collect();
-        let non_collapsed_positions = p!(0, 0).iter_to(p!(width - 1, height - 1)).collect();
+        let num_cells = topology.num_cells();
+        let cells = (0..num_cells).map(|_| C::new(num_tiles)).collect();
+        let non_collapsed_cells = (0..num_cells).collect();
 
-        let weights = weights.to_vec();
+        let mut neighbours = Vec::new();
+        let mut neighbour_starts = Vec::with_capacity(num_cells + 1);
+
+        for cell in 0..num_cells {
+            neighbour_starts.push(neighbours.len());
+            neighbours.extend(topology.neighbours(cell));
+        }
+
+        neighbour_starts.push(neighbours.len());
+
+        let base_weights = match weight_map {
+            Some(weight_map) => (0..num_cells)
+                .flat_map(|cell| {
+                    let (position, tile_weights) = (topology.position(cell), &tile_weights);
+                    (0..num_tiles).map(move |i| tile_weights[i] * weight_map(position, i))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let entropies = match weight_map {
+            Some(_) => base_weights
+                .chunks(num_tiles.max(1))
+                .map(|weights| CellEntropy::from_weights(weights.iter().copied()))
+                .collect(),
+            // all cells have the same weights
+            None => vec![CellEntropy::from_weights(tile_weights.iter().copied()); num_cells],
+        };
 
         Board {
-            width,
-            height,
+            topology,
+            neighbours,
+            neighbour_starts,
             cells,
-            weights,
-            non_collapsed_positions,
+            num_tiles,
+            tile_weights,
+            base_weights,
+            cell_weights: Vec::new(),
+            entropies,
+            tie_breakers: vec![0; num_cells],
+            entropy_queue: None,
+            supports: Vec::new(),
+            initial_supports: Vec::new(),
+            removed_tiles: Vec::new(),
+            non_collapsed_cells,
+            decided_cells: vec![false; num_cells],
             propagation_queue: VecDeque::new(),
+            changed_cells: Vec::new(),
+        }
+    }
+
+    /// Track the supports of every tile for the given [AdjacencyPropagator], so it can be used by
+    /// [Board::propagate]. None disables this.
+    pub(crate) fn track_supports(
+        &mut self,
+        propagator: Option<&AdjacencyPropagator<G::Direction>>,
+    ) {
+        self.initial_supports = propagator
+            .map(|p| p.initial_supports())
+            .unwrap_or_default();
+        self.reset_supports();
+    }
+
+    fn reset_supports(&mut self) {
+        self.supports.clear();
+
+        if !self.initial_supports.is_empty() {
+            for _ in 0..self.cells.len() {
+                self.supports.extend_from_slice(&self.initial_supports);
+            }
         }
     }
 
+    /// Remember that the given tile was removed from the given cell, so the removal
+    /// can be propagated by the [AdjacencyPropagator]. Does nothing if no supports are tracked.
+    fn tile_removed(
+        &mut self,
+        cell: usize,
+        tile: u16,
+    ) {
+        if self.initial_supports.is_empty() {
+            return;
+        }
+
+        self.clear_supports(cell, tile);
+        self.removed_tiles.push((cell, tile));
+    }
+
+    /// Set the supports of the given tile in the given cell to zero. A removed tile has no supports,
+    /// so they never drop to zero again.
+    fn clear_supports(
+        &mut self,
+        cell: usize,
+        tile: u16,
+    ) {
+        let start = cell * self.initial_supports.len();
+        let entries = AdjacencyPropagator::<G::Direction>::tile_entries(tile);
+        self.supports[(start + entries.start)..(start + entries.end)].fill(0);
+    }
+
+    /// Remove every tile which has no support in a direction where its cell has a neighbour, as no tile of
+    /// the neighbour can ever be next to it, and propagate the removals. Does nothing if no supports are tracked.
+    ///
+    /// Afterward, a support of zero always means that the tile was removed, which the propagation relies on.
+    /// Must be called after the supports were tracked and after every reset.
+    pub(crate) fn remove_unsupported_tiles<T>(
+        &mut self,
+        propagator: Option<&AdjacencyPropagator<G::Direction>>,
+        observers: &mut Observers<T, G::Position>,
+    ) -> Result<(), WfcError<G::Position>> {
+        let Some(propagator) = propagator else {
+            return Ok(());
+        };
+
+        for cell_index in 0..self.cells.len() {
+            for tile in 0..self.num_tiles as u16 {
+                let unsupported = self.neighbours[self.neighbour_range(cell_index)]
+                    .iter()
+                    .any(|(dir, _)| {
+                        self.initial_supports[AdjacencyPropagator::index(tile, *dir)] == 0
+                    });
+
+                if unsupported {
+                    self.remove_unsupported_tile(cell_index, tile, observers)?;
+                }
+            }
+        }
+
+        self.propagate_removed_tiles(propagator, observers)
+    }
+
+    /// Keep the not collapsed cells ordered by their entropy according to the given [EntropyHeuristic],
+    /// so [Board::get_min_entropy_position] doesn't have to look at every cell. None disables this.
+    pub(crate) fn track_entropy(
+        &mut self,
+        heuristic: Option<EntropyHeuristic>,
+    ) {
+        self.entropy_queue = heuristic.map(EntropyQueue::new);
+        self.rebuild_entropy_queue();
+    }
+
+    /// Draw a new tie breaker for every cell from the given [Random].
+    pub(crate) fn randomize_tie_breakers(
+        &mut self,
+        random: &mut Random,
+    ) {
+        for tie_breaker in self.tie_breakers.iter_mut() {
+            *tie_breaker = random.tie_breaker();
+        }
+
+        // the order of the queue depends on the tie breakers
+        self.rebuild_entropy_queue();
+    }
+
+    /// Fill the entropy queue with all not collapsed cells, which also removes all outdated entries.
+    fn rebuild_entropy_queue(&mut self) {
+        let Some(mut queue) = self.entropy_queue.take() else {
+            return;
+        };
+
+        let heuristic = queue.heuristic();
+        queue.rebuild(self.non_collapsed_cells.iter().map(|cell| {
+            (
+                *cell,
+                self.get_entropy(*cell, heuristic),
+                self.tie_breakers[*cell],
+            )
+        }));
+
+        self.entropy_queue = Some(queue);
+    }
+
+    /// Add the current entropy of the given cell to the entropy queue.
+    fn queue_entropy(
+        &mut self,
+        cell: usize,
+    ) {
+        let Some(heuristic) = self.entropy_queue.as_ref().map(|q| q.heuristic()) else {
+            return;
+        };
+
+        let entropy = self.get_entropy(cell, heuristic);
+        let tie_breaker = self.tie_breakers[cell];
+
+        let Some(queue) = &mut self.entropy_queue else {
+            return;
+        };
+
+        queue.push(cell, entropy, tie_breaker);
+
+        // the outdated entries stay in the queue until they reach its front. Without removing them, the
+        // queue would grow with every change of an entropy instead of with the amount of cells
+        if queue.len() > 3 * self.non_collapsed_cells.len() {
+            self.rebuild_entropy_queue();
+        }
+    }
+
+    /// Remove the outdated entries from the front of the entropy queue, which belong to cells which
+    /// are collapsed by now or whose entropy changed afterward.
+    fn prune_entropy_queue(&mut self) {
+        let Some(mut queue) = self.entropy_queue.take() else {
+            return;
+        };
+
+        let heuristic = queue.heuristic();
+
+        queue.prune(|cell, entropy| {
+            !self.cells[cell].is_collapsed() && self.get_entropy(cell, heuristic) == entropy
+        });
+
+        self.entropy_queue = Some(queue);
+    }
+
     /// tells if the full board is collapsed
     pub fn collapsed(&self) -> bool {
-        self.non_collapsed_positions.is_empty()
+        self.non_collapsed_cells.is_empty()
     }
 
-    /// Collapse the cell at the given position and set its tile index to the given one
-    pub fn collapse_position(
+    /// Collapse the given cell and set its tile index to the given one
+    pub fn collapse_cell(
         &mut self,
-        position: Position,
-        index: u8,
+        cell: usize,
+        index: u16,
     ) {
-        self.get_cell_mut(position).collapse(index);
-        self.non_collapsed_positions.remove(&position);
+        if !self.initial_supports.is_empty() {
+            // push the removals onto the preallocated stack directly, as tile_removed cannot be called
+            // while the possible indices of the cell are borrowed
+            let first_removed = self.removed_tiles.len();
+            self.removed_tiles.extend(
+                self.cells[cell]
+                    .get_possible_indices()
+                    .iter()
+                    .filter(|i| *i != index)
+                    .map(|tile| (cell, tile)),
+            );
+
+            for i in first_removed..self.removed_tiles.len() {
+                self.clear_supports(cell, self.removed_tiles[i].1);
+            }
+        }
+
+        self.cells[cell].collapse(index);
+        self.non_collapsed_cells.remove(&cell);
+        self.decided_cells[cell] = true;
+        self.prune_entropy_queue();
     }
 
-    /// Adapt all the cardinal neighbours of the given collapsed position
+    /// Return the range of the neighbours of the given cell in `neighbours`.
+    fn neighbour_range(
+        &self,
+        cell: usize,
+    ) -> Range<usize> {
+        self.neighbour_starts[cell]..self.neighbour_starts[cell + 1]
+    }
+
+    /// Adapt all the neighbours of the given collapsed cell
     /// This works recursive, so a collapsed neighbour will propagate the collapse to all
     /// its neighbours
     pub(crate) fn propagate<T>(
         &mut self,
-        collapsed_position: Position,
-        tile_constraints: &mut TileConstraints<T>,
+        collapsed_cell: usize,
+        tile_constraints: &mut TileConstraints<T, G>,
         all_tiles: &[T],
-    ) -> Result<(), WfcError> {
-        // init the propagation queue with the just collapsed position
-        self.propagation_queue.push_back(collapsed_position);
+        observers: &mut Observers<T, G::Position>,
+    ) -> Result<(), WfcError<G::Position>> {
+        // pure adjacency rules are propagated by the removed tiles instead of the changed cells
+        if let Some(propagator) = tile_constraints.propagator() {
+            return self.propagate_removed_tiles(propagator, observers);
+        }
 
-        // process the queue until nothing needs propagation anymore
-        while !self.propagation_queue.is_empty() {
-            let collapsed_position = self.propagation_queue.pop_front().unwrap();
+        // init the propagation queue with the just collapsed cell
+        self.propagation_queue.push_back(collapsed_cell);
 
+        // process the queue until nothing needs propagation anymore
+        while let Some(collapsed_cell) = self.propagation_queue.pop_front() {
             // go over all the neighbours to check if they can be updated
-            for pos in collapsed_position.cardinal_neighbours() {
-                // if on the edge, the neighbour might be out of bounds, so
-                // skip in this case
-                if !self.pos_in_bounds(pos) {
-                    continue;
-                }
+            for neighbour_index in self.neighbour_range(collapsed_cell) {
+                let (_, cell_index) = self.neighbours[neighbour_index];
 
-                // ignore already collapsed neighbours
-                if self.get_cell(pos).is_collapsed() {
+                // ignore neighbours whose tile was decided already
+                if self.decided_cells[cell_index] {
                     continue;
                 }
 
                 // Collect the relevant data from the neighbour cell to
                 // create a cell update for it, which is its next state
-                let neighbours = pos
-                    .cardinal_neighbours()
-                    .into_iter()
-                    .filter(|p| self.pos_in_bounds(*p))
-                    .map(|p| (self.get_cell(p).get_possible_indices(), p));
-                let cell = self.get_cell(pos);
-                let cell_indices = cell.get_possible_indices();
-                let new_indices = tile_constraints.update_cell(
+                let neighbours = self.neighbours[self.neighbour_range(cell_index)]
+                    .iter()
+                    .map(|(dir, n)| (self.cells[*n].get_possible_indices(), *dir));
+                let pos = self.topology.position(cell_index);
+                let cell_indices = self.cells[cell_index].get_possible_indices();
+                let (new_indices, new_modifiers) = tile_constraints.update_cell(
                     (cell_indices, pos),
                     neighbours,
                     all_tiles,
@@ -110,70 +377,484 @@ impl<C: Cell> Board<C> {
                 // no tile which fulfills all constraints. This is an error
                 // and is returned to the caller
                 if new_indices.is_empty() {
+                    observers.contradiction_found(pos);
+                    self.propagation_queue.clear();
+                    self.prune_entropy_queue();
                     return Err(WfcError::CellHasZeroEntropy(pos));
                 }
 
+                let indices_changed = cell_indices.entropy() as usize != new_indices.len();
+
+                if indices_changed {
+                    observers.possibilities_removed(pos, cell_indices, new_indices);
+                }
+
+                // The weight modifiers might have changed, even if the indices stayed the same
+                let weights_start = cell_index * self.num_tiles;
+                let old_entropy = self.entropies[cell_index].entropy();
+                let mut entropy = CellEntropy::default();
+
+                for (index, modifier) in new_indices.iter().zip(new_modifiers) {
+                    let weight = self.base_weight(cell_index, *index) * modifier;
+
+                    if *modifier != 1.0 && self.cell_weights.is_empty() {
+                        self.init_cell_weights();
+                    }
+
+                    if !self.cell_weights.is_empty() {
+                        self.cell_weights[weights_start + *index as usize] = weight;
+                    }
+
+                    entropy.add(weight);
+                }
+
+                self.entropies[cell_index] = entropy;
+
                 // If the indices changed (which can only mean: there are
                 // now fewer tiles), the cell has now a lower entropy. This
                 // must be propagated to its neighbours, so it is added to the
                 // propagation queue
-                if cell_indices.entropy() as usize != new_indices.len() {
-                    self.propagation_queue.push_back(pos);
+                if indices_changed {
+                    self.propagation_queue.push_back(cell_index);
+                    self.changed_cells.push(cell_index);
                 } else {
+                    // only the weights changed, which might still change the Shannon entropy
+                    if entropy.entropy() != old_entropy && !self.cells[cell_index].is_collapsed() {
+                        self.queue_entropy(cell_index);
+                    }
+
                     continue;
                 }
 
                 // update the cell with the values from the cell update
-                let cell_mut = self.get_cell_mut(pos);
-                cell_mut.set_indices(new_indices.iter().copied());
-
-                let cell = self.get_cell(pos);
+                let cell = &mut self.cells[cell_index];
+                cell.set_indices(new_indices.iter().copied());
 
                 if cell.is_collapsed() {
-                    self.non_collapsed_positions.remove(&pos);
+                    self.non_collapsed_cells.remove(&cell_index);
+                } else {
+                    self.queue_entropy(cell_index);
                 }
             }
         }
 
+        self.prune_entropy_queue();
+
+        Ok(())
+    }
+
+    /// Propagate all removed tiles with the AC-4 algorithm, see [AdjacencyPropagator].
+    fn propagate_removed_tiles<T>(
+        &mut self,
+        propagator: &AdjacencyPropagator<G::Direction>,
+        observers: &mut Observers<T, G::Position>,
+    ) -> Result<(), WfcError<G::Position>> {
+        let supports_per_cell = self.initial_supports.len();
+
+        while let Some((removed_cell, removed_tile)) = self.removed_tiles.pop() {
+            for neighbour_index in self.neighbour_range(removed_cell) {
+                let (dir, cell_index) = self.neighbours[neighbour_index];
+
+                // like in the regular propagation, decided neighbours are ignored
+                if self.decided_cells[cell_index] {
+                    continue;
+                }
+
+                // the removed tile supported all its compatible tiles in the neighbour from the opposite direction
+                for tile in propagator.compatible(removed_tile, dir) {
+                    let support_index = cell_index * supports_per_cell
+                        + AdjacencyPropagator::index(*tile, dir.opposite());
+                    let support = &mut self.supports[support_index];
+
+                    // a support of zero means the tile is removed already
+                    if *support == 0 {
+                        continue;
+                    }
+
+                    *support -= 1;
+
+                    if *support > 0 {
+                        continue;
+                    }
+
+                    self.remove_unsupported_tile(cell_index, *tile, observers)?;
+                }
+            }
+        }
+
+        self.prune_entropy_queue();
+
+        Ok(())
+    }
+
+    /// Remove the given tile from the given cell, as it lost all supports in a direction, and remember
+    /// the removal so it gets propagated as well.
+    fn remove_unsupported_tile<T>(
+        &mut self,
+        cell_index: usize,
+        tile: u16,
+        observers: &mut Observers<T, G::Position>,
+    ) -> Result<(), WfcError<G::Position>> {
+        let cell = &mut self.cells[cell_index];
+        cell.remove_index(tile);
+        observers.index_removed(self.topology.position(cell_index), tile);
+
+        if cell.entropy() == 0 {
+            let pos = self.topology.position(cell_index);
+            observers.contradiction_found(pos);
+            self.removed_tiles.clear();
+            self.prune_entropy_queue();
+            return Err(WfcError::CellHasZeroEntropy(pos));
+        }
+
+        let collapsed = cell.is_collapsed();
+
+        if collapsed {
+            self.non_collapsed_cells.remove(&cell_index);
+        }
+
+        match collapsed {
+            // start from scratch, so the rounding errors of the removals don't add up
+            true => {
+                let weight = self.weight(cell_index, self.cells[cell_index].get_collapsed_index());
+                self.entropies[cell_index] = CellEntropy::from_weights([weight]);
+            }
+            false => {
+                let weight = self.weight(cell_index, tile);
+                self.entropies[cell_index].remove(weight);
+            }
+        }
+
+        if !collapsed {
+            self.queue_entropy(cell_index);
+        }
+
+        self.changed_cells.push(cell_index);
+        self.tile_removed(cell_index, tile);
+
         Ok(())
     }
 
-    fn pos_in_bounds(
+    /// Remove the given tile index from the given cell, as it was
+    /// proven to lead to a contradiction.
+    pub(crate) fn remove_index<T>(
+        &mut self,
+        cell_index: usize,
+        index: u16,
+        observers: &mut Observers<T, G::Position>,
+    ) -> Result<(), WfcError<G::Position>> {
+        let pos = self.topology.position(cell_index);
+        let cell = &mut self.cells[cell_index];
+        let old_entropy = cell.entropy();
+        cell.remove_index(index);
+        let removed = cell.entropy() != old_entropy;
+
+        if removed {
+            observers.index_removed(pos, index);
+        }
+
+        if cell.entropy() == 0 {
+            observers.contradiction_found(pos);
+            return Err(WfcError::CellHasZeroEntropy(pos));
+        }
+
+        let collapsed = cell.is_collapsed();
+
+        if collapsed {
+            self.non_collapsed_cells.remove(&cell_index);
+        }
+
+        let entropy = CellEntropy::from_weights(
+            self.cells[cell_index]
+                .get_possible_indices()
+                .iter()
+                .map(|i| self.weight(cell_index, i)),
+        );
+        self.entropies[cell_index] = entropy;
+
+        if !collapsed {
+            self.queue_entropy(cell_index);
+        }
+
+        if removed {
+            self.tile_removed(cell_index, index);
+        }
+
+        self.prune_entropy_queue();
+        self.changed_cells.push(cell_index);
+
+        Ok(())
+    }
+
+    /// Return all positions whose possible tiles changed since the last call to [Board::clear_changed_positions].
+    ///
+    /// Every position is returned once, ordered by its cell index.
+    pub(crate) fn changed_positions(&mut self) -> impl Iterator<Item = G::Position> {
+        // a cell can change several times, like losing its tiles one by one, but should only be reported once
+        self.changed_cells.sort();
+        self.changed_cells.dedup();
+
+        self.changed_cells
+            .iter()
+            .map(|cell| self.topology.position(*cell))
+    }
+
+    pub(crate) fn clear_changed_positions(&mut self) {
+        self.changed_cells.clear();
+    }
+
+    /// Create a [BoardSnapshot] of the current state of the board.
+    pub(crate) fn snapshot(&self) -> BoardSnapshot<C> {
+        BoardSnapshot {
+            cells: self.cells.clone(),
+            cell_weights: self.cell_weights.clone(),
+            entropies: self.entropies.clone(),
+            supports: self.supports.clone(),
+            non_collapsed_cells: self.non_collapsed_cells.clone(),
+            decided_cells: self.decided_cells.clone(),
+        }
+    }
+
+    /// Write the current state of the board into an existing [BoardSnapshot], reusing its allocations.
+    pub(crate) fn snapshot_into(
         &self,
-        pos: Position,
-    ) -> bool {
-        pos.x >= 0 && pos.y >= 0 && pos.x < self.width as isize && pos.y < self.height as isize
+        snapshot: &mut BoardSnapshot<C>,
+    ) {
+        snapshot.cells.clone_from(&self.cells);
+        snapshot.cell_weights.clone_from(&self.cell_weights);
+        snapshot.entropies.clone_from(&self.entropies);
+        snapshot.supports.clone_from(&self.supports);
+        snapshot
+            .non_collapsed_cells
+            .clone_from(&self.non_collapsed_cells);
+        snapshot.decided_cells.clone_from(&self.decided_cells);
     }
 
-    pub fn get_min_entropy_position(&self) -> (Position, &C) {
-        self.non_collapsed_positions
+    /// Reset the board to the state of the given [BoardSnapshot].
+    ///
+    /// The entropy queue is not part of the snapshot, as it can be rebuilt from the restored cells.
+    pub(crate) fn restore(
+        &mut self,
+        snapshot: &BoardSnapshot<C>,
+    ) {
+        self.cells.clone_from(&snapshot.cells);
+        self.cell_weights.clone_from(&snapshot.cell_weights);
+        self.entropies.clone_from(&snapshot.entropies);
+        self.supports.clone_from(&snapshot.supports);
+        self.removed_tiles.clear();
+        self.non_collapsed_cells
+            .clone_from(&snapshot.non_collapsed_cells);
+        self.decided_cells.clone_from(&snapshot.decided_cells);
+        self.propagation_queue.clear();
+        self.changed_cells.clear();
+        self.rebuild_entropy_queue();
+    }
+
+    /// Return the not collapsed position with the lowest entropy according to the given [EntropyHeuristic].
+    pub fn get_min_entropy_position(
+        &self,
+        heuristic: EntropyHeuristic,
+    ) -> G::Position {
+        if let Some(queue) = &self.entropy_queue
+            && queue.heuristic() == heuristic
+        {
+            let cell = queue
+                .peek()
+                .expect("At least one non collapsed cell should exist");
+            return self.topology.position(cell);
+        }
+
+        let cells = self.non_collapsed_cells.iter().copied();
+
+        let cell = match heuristic {
+            EntropyHeuristic::RemainingTiles => {
+                cells.min_by_key(|cell| (self.cells[*cell].entropy(), self.tie_breakers[*cell]))
+            }
+            EntropyHeuristic::Shannon => cells.min_by(|a, b| {
+                self.entropies[*a]
+                    .entropy()
+                    .total_cmp(&self.entropies[*b].entropy())
+                    .then_with(|| self.tie_breakers[*a].cmp(&self.tie_breakers[*b]))
+            }),
+        }
+        .expect("At least one non collapsed cell should exist");
+
+        self.topology.position(cell)
+    }
+
+    /// Return an [Iterator] over all positions which are not collapsed yet, ordered by their cell index.
+    pub(crate) fn non_collapsed_positions(&self) -> impl Iterator<Item = G::Position> {
+        self.non_collapsed_cells
             .iter()
-            .map(|pos| (*pos, self.get_cell(*pos)))
-            .min_by(|(_, cell_a), (_, cell_b)| cell_a.entropy().cmp(&cell_b.entropy()))
-            .expect("At least one non collapsed cell should exist")
+            .map(|cell| self.topology.position(*cell))
+    }
+
+    /// Return the entropy of the given cell according to the given [EntropyHeuristic].
+    fn get_entropy(
+        &self,
+        cell: usize,
+        heuristic: EntropyHeuristic,
+    ) -> f32 {
+        match heuristic {
+            EntropyHeuristic::RemainingTiles => self.cells[cell].entropy() as f32,
+            EntropyHeuristic::Shannon => self.entropies[cell].entropy(),
+        }
+    }
+
+    /// Return the cached Shannon entropy of the cell at the given position.
+    pub(crate) fn get_shannon_entropy(
+        &self,
+        pos: G::Position,
+    ) -> f32 {
+        self.entropies[self.cell_index(pos)].entropy()
     }
 
     pub fn get_cell(
         &self,
-        pos: Position,
+        pos: G::Position,
     ) -> &C {
-        self.cells
-            .get(self.width * pos.y as usize + pos.x as usize)
+        &self.cells[self.cell_index(pos)]
+    }
+
+    /// Return the current weight of the given tile index in the cell at the given position.
+    pub(crate) fn get_weight(
+        &self,
+        pos: G::Position,
+        index: u16,
+    ) -> f32 {
+        self.weight(self.cell_index(pos), index)
+    }
+
+    /// Return the current weight of the given tile in the given cell.
+    fn weight(
+        &self,
+        cell: usize,
+        tile: u16,
+    ) -> f32 {
+        match self.cell_weights.is_empty() {
+            true => self.base_weight(cell, tile),
+            false => self.cell_weights[cell * self.num_tiles + tile as usize],
+        }
+    }
+
+    /// Return the weight of the given tile in the given cell before any weight modifier was applied.
+    fn base_weight(
+        &self,
+        cell: usize,
+        tile: u16,
+    ) -> f32 {
+        match self.base_weights.is_empty() {
+            true => self.tile_weights[tile as usize],
+            false => self.base_weights[cell * self.num_tiles + tile as usize],
+        }
+    }
+
+    /// Fill the weights of every cell with their base weights, so weight modifiers can change them.
+    fn init_cell_weights(&mut self) {
+        let mut cell_weights = std::mem::take(&mut self.cell_weights);
+        let board = &*self;
+        cell_weights.extend((0..board.cells.len()).flat_map(|cell| {
+            (0..board.num_tiles as u16).map(move |tile| board.base_weight(cell, tile))
+        }));
+        self.cell_weights = cell_weights;
+    }
+
+    /// Return the index of the cell at the given position.
+    ///
+    /// Panics if no cell exists at the position.
+    pub(crate) fn cell_index(
+        &self,
+        pos: G::Position,
+    ) -> usize {
+        self.topology
+            .cell(pos)
             .unwrap_or_else(|| panic!("A cell at position {:?} should exist", pos))
     }
 
-    pub fn get_cell_mut(
-        &mut self,
-        pos: Position,
-    ) -> &mut C {
+    pub fn topology(&self) -> &G {
+        &self.topology
+    }
+
+    /// Return the collapsed tile index of every cell in the order of the cell indices.
+    pub fn get_collapsed_indices(&self) -> impl Iterator<Item = usize> + '_ {
         self.cells
-            .get_mut(self.width * pos.y as usize + pos.x as usize)
-            .unwrap_or_else(|| panic!("A cell at position {:?} should exist", pos))
+            .iter()
+            .map(|cell| cell.get_collapsed_index() as usize)
+    }
+}
+
+/// A read-only view of the board, which is used by a [crate::heuristic::SelectionHeuristic]
+/// to decide which cell gets collapsed next.
+pub struct BoardView<'a, C: Cell, G: Topology = SquareGrid> {
+    board: &'a Board<C, G>,
+}
+
+impl<C: Cell, G: Topology> Clone for BoardView<'_, C, G> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: Cell, G: Topology> Copy for BoardView<'_, C, G> {}
+
+impl<'a, C: Cell, G: Topology> BoardView<'a, C, G> {
+    pub(crate) fn new(board: &'a Board<C, G>) -> Self {
+        BoardView { board }
     }
 
-    pub fn get_collapsed_indices(&self) -> impl Iterator<Item = (Position, usize)> + '_ {
-        p!(0, 0)
-            .iter_to(p!(self.width - 1, self.height - 1))
-            .map(|pos| (pos, self.get_cell(pos).get_collapsed_index() as usize))
+    /// Return the [Topology] of the board, which tells its shape.
+    pub fn topology(&self) -> &'a G {
+        self.board.topology()
     }
+
+    /// Return the [Cell] at the given position.
+    ///
+    /// Panics if the position is out of bounds.
+    pub fn cell(
+        &self,
+        position: G::Position,
+    ) -> &'a C {
+        self.board.get_cell(position)
+    }
+
+    /// Return an [Iterator] over all positions which are not collapsed yet.
+    pub fn non_collapsed_positions(&self) -> impl Iterator<Item = G::Position> + use<'a, C, G> {
+        self.board.non_collapsed_positions()
+    }
+
+    /// Return the current weight of the given tile index at the given position.
+    pub fn weight(
+        &self,
+        position: G::Position,
+        index: u16,
+    ) -> f32 {
+        self.board.get_weight(position, index)
+    }
+
+    /// Return the weighted Shannon entropy of the cell at the given position.
+    pub fn shannon_entropy(
+        &self,
+        position: G::Position,
+    ) -> f32 {
+        self.board.get_shannon_entropy(position)
+    }
+
+    /// Return the not collapsed position with the lowest entropy according to the given [EntropyHeuristic].
+    /// Ties between positions with the same entropy are broken randomly.
+    pub fn min_entropy_position(
+        &self,
+        heuristic: EntropyHeuristic,
+    ) -> G::Position {
+        self.board.get_min_entropy_position(heuristic)
+    }
+}
+
+/// The state of a [Board] at a specific point in time, which can be restored later.
+pub(crate) struct BoardSnapshot<C: Cell> {
+    cells: Vec<C>,
+    cell_weights: Vec<f32>,
+    entropies: Vec<CellEntropy>,
+    supports: Vec<u16>,
+    non_collapsed_cells: BTreeSet<usize>,
+    decided_cells: Vec<bool>,
 }
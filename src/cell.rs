@@ -1,12 +1,15 @@
 use bitarray::BitArray;
 
 /// A [Cell] contains the possible tiles (or rather their indices) at a specific [pad::position::Position] on the [crate::board::Board].
-pub trait Cell {
+pub trait Cell: Clone {
+    /// The maximum amount of tiles this [Cell] supports.
+    const MAX_TILES: usize;
+
     /// Create a new [Cell] from the amount of tiles the wfc will use.
     fn new(num_tiles: usize) -> Self;
 
     /// Return the entropy of this [Cell], which is the amount of possible tiles.
-    fn entropy(&self) -> u8;
+    fn entropy(&self) -> u16;
 
     /// Return the current [PossibleIndices] of the [Cell]. This basically tells
     /// which tiles are still possible.
@@ -15,17 +18,24 @@ pub trait Cell {
     /// Collapse the [Cell] to the given tile index.
     fn collapse(
         &mut self,
-        index: u8,
+        index: u16,
     );
 
     /// Update the [Cell] by setting its possible indices to the given ones.
     fn set_indices(
         &mut self,
-        indices: impl IntoIterator<Item = u8>,
+        indices: impl IntoIterator<Item = u16>,
+    );
+
+    /// Remove the given tile index from the possible indices of this [Cell]. Does nothing
+    /// if the index is not possible anymore.
+    fn remove_index(
+        &mut self,
+        index: u16,
     );
 
     /// Return the last possible index in this [Cell], assuming it is collapsed.
-    fn get_collapsed_index(&self) -> u8;
+    fn get_collapsed_index(&self) -> u16;
 
     /// Returs true if this [Cell] is collapsed, or else false.
     fn is_collapsed(&self) -> bool;
@@ -39,26 +49,28 @@ pub trait Cell {
 #[derive(Clone, Copy)]
 pub struct ConstCell<const C: usize> {
     /// the current entropy of the cell, or the amount of still possible tiles
-    pub entropy: u8,
+    pub entropy: u16,
     /// the indices of all tiles which are currently possible in this cell; only the entries until the self.entropy index are used
-    tile_indices: [u8; C],
+    tile_indices: [u16; C],
 }
 
 impl<const C: usize> Cell for ConstCell<C> {
+    const MAX_TILES: usize = C;
+
     fn new(num_tiles: usize) -> Self {
         let mut tile_indices = [0; C];
 
         for (i, index) in tile_indices.iter_mut().enumerate() {
-            *index = i as u8
+            *index = i as u16
         }
 
         ConstCell {
-            entropy: num_tiles as u8,
+            entropy: num_tiles as u16,
             tile_indices,
         }
     }
 
-    fn entropy(&self) -> u8 {
+    fn entropy(&self) -> u16 {
         self.entropy
     }
 
@@ -68,7 +80,7 @@ impl<const C: usize> Cell for ConstCell<C> {
 
     fn collapse(
         &mut self,
-        index: u8,
+        index: u16,
     ) {
         self.tile_indices[0] = index;
         self.entropy = 1;
@@ -76,13 +88,13 @@ impl<const C: usize> Cell for ConstCell<C> {
 
     fn set_indices(
         &mut self,
-        indices: impl IntoIterator<Item = u8>,
+        indices: impl IntoIterator<Item = u16>,
     ) {
         let mut entropy = 0;
 
         for (i, index) in indices.into_iter().enumerate() {
-            if index == u8::MAX {
-                // usize::MAX is used as a placeholder. The first occurrence
+            if index == u16::MAX {
+                // u16::MAX is used as a placeholder. The first occurrence
                 // of it tells that there are no more indices to consider
                 break;
             }
@@ -94,7 +106,23 @@ impl<const C: usize> Cell for ConstCell<C> {
         self.entropy = entropy
     }
 
-    fn get_collapsed_index(&self) -> u8 {
+    fn remove_index(
+        &mut self,
+        index: u16,
+    ) {
+        let entropy = self.entropy as usize;
+
+        if let Some(i) = self.tile_indices[..entropy]
+            .iter()
+            .position(|i| *i == index)
+        {
+            // keep the order of the remaining indices
+            self.tile_indices.copy_within((i + 1)..entropy, i);
+            self.entropy -= 1;
+        }
+    }
+
+    fn get_collapsed_index(&self) -> u16 {
         self.tile_indices[0]
     }
 
@@ -107,11 +135,13 @@ impl<const C: usize> Cell for ConstCell<C> {
 /// is used as the base, this [Cell] supports up to 128 different tiles.
 #[derive(Clone, Copy)]
 pub struct BitCell {
-    pub entropy: u8,
+    pub entropy: u16,
     tile_indices: BitArray<u128>,
 }
 
 impl Cell for BitCell {
+    const MAX_TILES: usize = u128::BITS as usize;
+
     fn new(num_tiles: usize) -> Self {
         let mut tile_indices = BitArray::new(0);
 
@@ -120,12 +150,12 @@ impl Cell for BitCell {
         }
 
         BitCell {
-            entropy: num_tiles as u8,
+            entropy: num_tiles as u16,
             tile_indices,
         }
     }
 
-    fn entropy(&self) -> u8 {
+    fn entropy(&self) -> u16 {
         self.entropy
     }
 
@@ -135,28 +165,28 @@ impl Cell for BitCell {
 
     fn collapse(
         &mut self,
-        index: u8,
+        index: u16,
     ) {
         self.tile_indices = BitArray::new(0);
-        self.tile_indices.set(index, true);
+        self.tile_indices.set(index as u8, true);
         self.entropy = 1;
     }
 
     fn set_indices(
         &mut self,
-        indices: impl IntoIterator<Item = u8>,
+        indices: impl IntoIterator<Item = u16>,
     ) {
         let mut entropy = 0;
         let mut tile_indices = BitArray::new(0);
 
         for index in indices {
-            if index == u8::MAX {
-                // usize::MAX is used as a placeholder. The first occurrence
+            if index == u16::MAX {
+                // u16::MAX is used as a placeholder. The first occurrence
                 // of it tells that there are no more indices to consider
                 break;
             }
 
-            tile_indices.set(index, true);
+            tile_indices.set(index as u8, true);
             entropy += 1;
         }
 
@@ -164,8 +194,18 @@ impl Cell for BitCell {
         self.tile_indices = tile_indices
     }
 
-    fn get_collapsed_index(&self) -> u8 {
-        self.tile_indices.ones().next().unwrap()
+    fn remove_index(
+        &mut self,
+        index: u16,
+    ) {
+        if self.tile_indices.ones().any(|i| i as u16 == index) {
+            self.tile_indices.set(index as u8, false);
+            self.entropy -= 1;
+        }
+    }
+
+    fn get_collapsed_index(&self) -> u16 {
+        self.tile_indices.ones().next().unwrap() as u16
     }
 
     fn is_collapsed(&self) -> bool {
@@ -173,25 +213,28 @@ impl Cell for BitCell {
     }
 }
 
-/// A [Cell] which supports an arbitrary amount of tiles. This is the most flexible implementation
-/// of [Cell]
+/// A [Cell] which supports an arbitrary amount of tiles, up to [u16::MAX]. This is the most flexible
+/// implementation of [Cell]
+#[derive(Clone)]
 pub struct DynCell {
-    indices: Vec<u8>
+    indices: Vec<u16>
 }
 
 impl Cell for DynCell {
+    const MAX_TILES: usize = u16::MAX as usize;
+
     fn new(num_tiles: usize) -> Self {
         let mut indices = Vec::with_capacity(num_tiles);
 
         for i in 0..num_tiles {
-            indices.push(i as u8);
+            indices.push(i as u16);
         }
 
         DynCell { indices }
     }
 
-    fn entropy(&self) -> u8 {
-        self.indices.len() as u8
+    fn entropy(&self) -> u16 {
+        self.indices.len() as u16
     }
 
     fn get_possible_indices(&self) -> PossibleIndices<'_> {
@@ -200,7 +243,7 @@ impl Cell for DynCell {
 
     fn collapse(
         &mut self,
-        index: u8,
+        index: u16,
     ) {
         self.indices.clear();
         self.indices.push(index);
@@ -208,13 +251,20 @@ impl Cell for DynCell {
 
     fn set_indices(
         &mut self,
-        indices: impl IntoIterator<Item = u8>,
+        indices: impl IntoIterator<Item = u16>,
     ) {
         self.indices.clear();
         indices.into_iter().for_each(|index| self.indices.push(index));
     }
 
-    fn get_collapsed_index(&self) -> u8 {
+    fn remove_index(
+        &mut self,
+        index: u16,
+    ) {
+        self.indices.retain(|i| *i != index);
+    }
+
+    fn get_collapsed_index(&self) -> u16 {
         self.indices[0]
     }
 
@@ -225,8 +275,8 @@ impl Cell for DynCell {
 
 #[derive(Clone, Copy)]
 pub enum PossibleIndices<'a> {
-    Array { indices: &'a [u8], entropy: u8 },
-    BitArray { array: BitArray<u128>, entropy: u8 },
+    Array { indices: &'a [u16], entropy: u16 },
+    BitArray { array: BitArray<u128>, entropy: u16 },
 }
 
 impl<'a> Default for PossibleIndices<'a> {
@@ -239,37 +289,40 @@ impl<'a> Default for PossibleIndices<'a> {
 
 impl<'a> PossibleIndices<'a> {
     fn from_array(
-        indices: &'a [u8],
-        entropy: u8,
+        indices: &'a [u16],
+        entropy: u16,
     ) -> Self {
         PossibleIndices::Array { indices, entropy }
     }
 
     fn from_bitarray(
         array: BitArray<u128>,
-        entropy: u8,
+        entropy: u16,
     ) -> Self {
         PossibleIndices::BitArray { array, entropy }
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = u8> {
+    pub fn iter(&self) -> impl Iterator<Item = u16> {
         PossibleIndicesIter::new(*self)
     }
 
+    /// Return the possible index at the given position. The bitarray has to be searched for it, so
+    /// prefer [PossibleIndices::iter] to visit all indices.
     pub fn get(
         &self,
         index: usize,
-    ) -> u8 {
+    ) -> u16 {
         match self {
             PossibleIndices::Array { indices, .. } => indices[index],
             PossibleIndices::BitArray { array, .. } => array
                 .ones()
                 .nth(index)
-                .expect("The index should be in bounds of the bitarray"),
+                .expect("The index should be in bounds of the bitarray")
+                as u16,
         }
     }
 
-    pub fn entropy(&self) -> u8 {
+    pub fn entropy(&self) -> u16 {
         match self {
             PossibleIndices::Array { entropy, .. } => *entropy,
             PossibleIndices::BitArray { entropy, .. } => *entropy,
@@ -281,29 +334,55 @@ impl<'a> PossibleIndices<'a> {
     }
 }
 
+impl<'a> IntoIterator for PossibleIndices<'a> {
+    type Item = u16;
+    type IntoIter = PossibleIndicesIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        PossibleIndicesIter::new(self)
+    }
+}
+
 pub struct PossibleIndicesIter<'a> {
-    counter: u8,
+    counter: u16,
     possible_indices: PossibleIndices<'a>,
+    /// The bits of the bitarray which were not returned yet. Unused for arrays.
+    remaining_bits: u128,
 }
 
 impl<'a> PossibleIndicesIter<'a> {
     fn new(possible_indices: PossibleIndices<'a>) -> Self {
+        let remaining_bits = match possible_indices {
+            PossibleIndices::Array { .. } => 0,
+            PossibleIndices::BitArray { array, .. } => array.value(),
+        };
+
         PossibleIndicesIter {
             counter: 0,
             possible_indices,
+            remaining_bits,
         }
     }
 }
 
 impl<'a> Iterator for PossibleIndicesIter<'a> {
-    type Item = u8;
+    type Item = u16;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.counter == self.possible_indices.entropy() {
             return None;
         }
 
-        let elem = self.possible_indices.get(self.counter as usize);
+        let elem = match self.possible_indices {
+            PossibleIndices::Array { indices, .. } => indices[self.counter as usize],
+            // the lowest remaining bit is the next index. Clearing it makes the bit after it the lowest one
+            PossibleIndices::BitArray { .. } => {
+                let index = self.remaining_bits.trailing_zeros() as u16;
+                self.remaining_bits &= self.remaining_bits - 1;
+                index
+            }
+        };
+
         self.counter += 1;
         Some(elem)
     }
@@ -313,7 +392,7 @@ impl<'a> Iterator for PossibleIndicesIter<'a> {
 mod tests {
     use bitarray::BitArray;
 
-    use crate::cell::PossibleIndices;
+    use crate::cell::{Cell, DynCell, PossibleIndices};
 
     #[test]
     fn possible_indices_iter_works() {
@@ -326,8 +405,20 @@ mod tests {
         bitarray.set(2, true);
         bitarray.set(4, true);
         bitarray.set(6, true);
-        let bitarray_indices = PossibleIndices::from_bitarray(bitarray, 3);
+        bitarray.set(127, true);
+        let bitarray_indices = PossibleIndices::from_bitarray(bitarray, 4);
         let iter = bitarray_indices.iter();
-        assert_eq!(iter.collect::<Vec<_>>(), vec![2, 4, 6])
+        assert_eq!(iter.collect::<Vec<_>>(), vec![2, 4, 6, 127]);
+        assert_eq!(bitarray_indices.get(3), 127)
+    }
+
+    #[test]
+    fn dyn_cell_supports_many_tiles() {
+        let mut cell = DynCell::new(1000);
+        assert_eq!(cell.entropy(), 1000);
+
+        cell.remove_index(999);
+        cell.collapse(300);
+        assert_eq!(cell.get_collapsed_index(), 300);
     }
 }
@@ -1,92 +1,182 @@
-use pad::position::Position;
+use std::marker::PhantomData;
 
+use crate::bitmask::TileMasks;
 use crate::cell::PossibleIndices;
+use crate::direction::{Direction, NeighbourDirection};
+use crate::grid::WfcGrid;
+use crate::propagator::AdjacencyPropagator;
+use crate::sample::SampleRules;
+use crate::sockets::{Socket, Sockets};
+use crate::symmetry::{Orientation, Oriented, Symmetric};
+use crate::topology::{Masked, SquareGrid, Topology};
+
+/// The amount of neighbours of a cell which are collected on the stack in [TileConstraints::update_cell].
+/// Cells with more neighbours are only possible in custom topologies and need an allocation.
+const MAX_STACK_NEIGHBOURS: usize = 8;
 
 /// Wrapper around the list of [Constraint]s which are configured in the [crate::Wfc].
-pub(crate) struct TileConstraints<T> {
-    constraints: Vec<Box<dyn Constraint<T>>>,
+pub(crate) struct TileConstraints<T, G: Topology> {
+    constraints: Vec<Box<dyn Constraint<T, G>>>,
     /// The preallocated Vec of new indices for a cell
-    new_indices: Vec<u8>
+    new_indices: Vec<u16>,
+    /// The preallocated Vec of weight modifiers for the new indices of a cell
+    new_modifiers: Vec<f32>,
+    /// The compiled constraints, if all of them only decide which tiles can be next to each other
+    propagator: Option<AdjacencyPropagator<G::Direction>>,
+    /// Prevents the compilation, so tests can compare the [AdjacencyPropagator] with the regular propagation
+    #[cfg(test)]
+    pub(crate) skip_compilation: bool,
 }
 
-impl<T> Default for TileConstraints<T> {
+impl<T, G: Topology> Default for TileConstraints<T, G> {
     fn default() -> Self {
         TileConstraints {
             constraints: vec![],
-            new_indices: vec![]
+            new_indices: vec![],
+            new_modifiers: vec![],
+            propagator: None,
+            #[cfg(test)]
+            skip_compilation: false,
         }
     }
 }
 
-impl<T> TileConstraints<T> {
+impl<T, G: Topology> TileConstraints<T, G> {
     pub(crate) fn add_constraint(
         &mut self,
-        constraint: impl Constraint<T> + 'static,
+        constraint: impl Constraint<T, G> + 'static,
     ) {
         self.constraints.push(Box::new(constraint));
     }
 
+    /// Compile the constraints into an [AdjacencyPropagator], if all of them can be expressed as
+    /// [DirectionalNeighbours] and the topology has at most one neighbour per direction
+    /// (see [Topology::unique_directions]). Must be called after all constraints were added.
+    pub(crate) fn compile(
+        &mut self,
+        tiles: &[T],
+        topology: &G,
+    ) {
+        #[cfg(test)]
+        if self.skip_compilation {
+            return;
+        }
+
+        if !topology.unique_directions() {
+            self.propagator = None;
+            return;
+        }
+
+        let rules = self
+            .constraints
+            .iter()
+            .map(|c| c.as_directional_neighbours(tiles))
+            .collect::<Option<Vec<_>>>();
+
+        self.propagator = rules
+            .filter(|rules| !rules.is_empty())
+            .map(|rules| AdjacencyPropagator::new(tiles.len(), &rules));
+    }
+
+    /// Return the compiled constraints, see [TileConstraints::compile].
+    pub(crate) fn propagator(&self) -> Option<&AdjacencyPropagator<G::Direction>> {
+        self.propagator.as_ref()
+    }
+
+    /// Move the constraints to the [Masked] version of the topology, which has the same positions and directions.
+    /// Must be called before the constraints are compiled.
+    pub(crate) fn masked(self) -> TileConstraints<T, Masked<G>>
+    where
+        T: 'static,
+        G: 'static,
+    {
+        TileConstraints {
+            constraints: self
+                .constraints
+                .into_iter()
+                .map(|c| Box::new(MaskedConstraint(c)) as Box<dyn Constraint<T, Masked<G>>>)
+                .collect(),
+            ..TileConstraints::default()
+        }
+    }
+
     /// Determine the next values for a cell based on the set constraints.
     /// * `(cell_tiles, cell_position)` - The currently possible tiles and the position on the board of the cell to check.
-    /// * `neighbours_iter` - An iterator over all the neighbours around the cell to check.
+    /// * `neighbours_iter` - An iterator over all the neighbours around the cell to check and their directions.
     /// * `tiles` - A slice of all tiles set in the wfc. Used to access a reference of a tile at a given index.
+    ///
+    /// Returns the new indices of the cell and the weight modifier for each of them.
     pub fn update_cell<'a>(
         &mut self,
-        (cell_tiles, cell_position): (PossibleIndices<'a>, Position),
-        neighbours_iter: impl IntoIterator<Item = (PossibleIndices<'a>, Position)>,
+        (cell_tiles, cell_position): (PossibleIndices<'a>, G::Position),
+        neighbours_iter: impl IntoIterator<Item = (PossibleIndices<'a>, G::Direction)>,
         tiles: &[T],
-    ) -> &[u8] {
-        // collect all the neighbours in an array
-        let mut neighbours: [(PossibleIndices, Position); 4] =
-            [(PossibleIndices::default(), Position::default()); 4];
+    ) -> (&[u16], &[f32]) {
+        // collect all the neighbours in an array, and only fall back to a Vec if there are too many
+        let mut neighbours_array = [(
+            PossibleIndices::default(),
+            <G::Direction as NeighbourDirection>::ALL[0],
+        ); MAX_STACK_NEIGHBOURS];
+        let mut neighbours_vec = Vec::new();
         let mut num_neighbours = 0;
 
-        for (i, (possible_tiles, pos)) in neighbours_iter.into_iter().enumerate() {
-            neighbours[i] = (possible_tiles, pos);
+        for neighbour in neighbours_iter {
+            if num_neighbours < MAX_STACK_NEIGHBOURS {
+                neighbours_array[num_neighbours] = neighbour;
+            } else {
+                if neighbours_vec.is_empty() {
+                    neighbours_vec.extend_from_slice(&neighbours_array);
+                }
+
+                neighbours_vec.push(neighbour);
+            }
+
             num_neighbours += 1;
         }
 
-        // Clear the existing new indices
+        let neighbours = if num_neighbours <= MAX_STACK_NEIGHBOURS {
+            &neighbours_array[..num_neighbours]
+        } else {
+            &neighbours_vec[..]
+        };
+
+        // Clear the existing new indices and modifiers
         self.new_indices.clear();
+        self.new_modifiers.clear();
 
         // outer loop which iterates over all the currently possible tile indices
         // in the cell (also going wild by using a loop tag)
         'outer: for index in cell_tiles.iter() {
+            let mut modifier = 1.0;
+
             // inner loop to check if all constraints are fulfilled
             for c in self.constraints.iter() {
-                let valid = c.valid(
-                    (index, cell_position),
-                    &neighbours[0..num_neighbours],
-                    tiles,
-                );
+                let valid = c.valid((index, cell_position), neighbours, tiles);
 
-                if !valid {
-                    continue 'outer;
+                match valid {
+                    Some(m) => modifier *= m,
+                    None => continue 'outer,
                 }
             }
 
             // Update the new indices and weight. As the entropy
             // increments, it can also be used as the index here
             self.new_indices.push(index);
+            self.new_modifiers.push(modifier);
         }
 
-        &self.new_indices
+        (&self.new_indices, &self.new_modifiers)
     }
 }
 
 // todo Things I want as possible constraints:
 //  - The classic color constraint
-//  - Simple neighbourhood, just like currently
-//  - Directional neighbour restrictions, like the original coast example
-
-// todo the Constraint should not return a bool, but an optional weight modifier.
-//  - Every cell should hold the weights for their tiles
-//  - If the modifier is not None, the weight for that tile gets set accordingly
-//  - The initial weight for every tile is the setting provided to the WFC
-//  This allows for a "Bias Constraint", where based on the neighbours, some tile should have
-//  way higher or lower probability to get picked
 
-pub trait Constraint<T> {
+/// A rule which decides if a tile can be placed in a cell, depending on the possible tiles of its neighbours.
+///
+/// The constraint can be implemented for any [Topology]. Constraints which don't care about the shape of
+/// the board (like [PossibleNeighbours]) implement it for all of them.
+pub trait Constraint<T, G: Topology = SquareGrid> {
     /// Check for a specific tile and its given collapsed neighbour if it would be a valid
     /// remaining choice.  
     /// If the tile is valid, it returns a weight modifier. This modifier (alongside the modifiers
@@ -99,21 +189,60 @@ pub trait Constraint<T> {
     ///
     /// # Parameters
     /// * `tile_to_check` - The tile index and its position which I want to know would be valid according to this constraint
-    /// * `neighbours` - The possible tiles and their positions of all neighbours of the tile to check.
+    /// * `neighbours` - The possible tiles of all neighbours of the tile to check and the direction they lie in.
     /// * `tiles` - All actual possible tiles. This can be used to map the tile index to the actual tile for more complex logic
     ///
     /// Returns Some(weight_modifier) if the tile to check could be placed on this position, according to this constraint.
     fn valid(
         &self,
-        tile_to_check: (u8, Position),
-        neighbours: &[(PossibleIndices, Position)],
+        tile_to_check: (u16, G::Position),
+        neighbours: &[(PossibleIndices, G::Direction)],
+        tiles: &[T],
+    ) -> Option<f32>;
+
+    /// Return the rules of this constraint as [DirectionalNeighbours], if it only decides which tiles can be
+    /// next to each other and never modifies the weights.
+    ///
+    /// If this is possible for all constraints of the wfc, they are compiled into a compatibility table,
+    /// which is propagated with the AC-4 algorithm instead of calling [Constraint::valid]. This is a lot faster.
+    fn as_directional_neighbours(
+        &self,
+        _tiles: &[T],
+    ) -> Option<DirectionalNeighbours<G::Direction>> {
+        None
+    }
+}
+
+/// A [Constraint] of a topology, which is applied to the [Masked] version of it.
+struct MaskedConstraint<T, G: Topology>(Box<dyn Constraint<T, G>>);
+
+impl<T, G: Topology> Constraint<T, Masked<G>> for MaskedConstraint<T, G> {
+    fn valid(
+        &self,
+        tile_to_check: (u16, G::Position),
+        neighbours: &[(PossibleIndices, G::Direction)],
+        tiles: &[T],
+    ) -> Option<f32> {
+        self.0.valid(tile_to_check, neighbours, tiles)
+    }
+
+    fn as_directional_neighbours(
+        &self,
         tiles: &[T],
-    ) -> bool;
+    ) -> Option<DirectionalNeighbours<G::Direction>> {
+        self.0.as_directional_neighbours(tiles)
+    }
 }
 
 /// A [Constraint] which defines what tiles can be neighboured to each other.
+///
+/// The allowed neighbours of every tile are stored as a bitmask, so a neighbour cell can be checked
+/// with a bitwise AND if its possible indices are bits as well.
+#[derive(Clone)]
 pub struct PossibleNeighbours {
-    allowed_neighbours: Vec<(u8, u8)>,
+    num_tiles: usize,
+    /// For every tile, the mask of the tiles which are allowed as its neighbours
+    allowed_neighbours: TileMasks,
 }
 
 impl PossibleNeighbours {
@@ -121,35 +250,409 @@ impl PossibleNeighbours {
         allowed_neighbours: impl IntoIterator<Item = (T, T)>,
         all_tiles: &[T],
     ) -> Self {
-        let get_index = |tile: T| {
-            all_tiles
-                .iter()
-                .position(|t| *t == tile)
-                .expect("The tile should be in the possible tiles") as u8
-        };
+        Self::from_index_pairs(
+            all_tiles.len(),
+            allowed_neighbours
+                .into_iter()
+                .map(|(t0, t1)| (tile_index(all_tiles, t0), tile_index(all_tiles, t1))),
+        )
+    }
+
+    /// Learn the [PossibleNeighbours] from a sample. Two tiles can be neighbours if they are
+    /// next to each other somewhere in the sample.
+    pub fn from_sample<T: Clone + PartialEq>(sample: &WfcGrid<T>) -> SampleRules<T, Self> {
+        SampleRules::learn(sample, |num_tiles, neighbour_counts| {
+            // the direction does not matter
+            Self::from_index_pairs(
+                num_tiles,
+                neighbour_counts.iter().map(|(t0, _, t1, _)| (*t0, *t1)),
+            )
+        })
+    }
+
+    /// Create the [PossibleNeighbours] from pairs which already use the tile indices. Every pair
+    /// is allowed both ways.
+    fn from_index_pairs(
+        num_tiles: usize,
+        allowed_neighbours: impl IntoIterator<Item = (u16, u16)>,
+    ) -> Self {
+        let mut allowed = TileMasks::new(num_tiles, num_tiles);
+
+        for (t0, t1) in allowed_neighbours {
+            allowed.insert(t0 as usize, t1);
+            allowed.insert(t1 as usize, t0);
+        }
 
         PossibleNeighbours {
-            allowed_neighbours: allowed_neighbours
-                .into_iter()
-                .map(|(t0, t1)| (get_index(t0), get_index(t1)))
-                .collect(),
+            num_tiles,
+            allowed_neighbours: allowed,
         }
     }
+
+    /// Tells if the two tiles can be neighbours.
+    pub fn allowed(
+        &self,
+        tile: u16,
+        neighbour: u16,
+    ) -> bool {
+        self.allowed_neighbours.contains(tile as usize, neighbour)
+    }
 }
 
-impl<T> Constraint<T> for PossibleNeighbours {
+impl<T, G: Topology> Constraint<T, G> for PossibleNeighbours {
     fn valid(
         &self,
-        (tile, _): (u8, Position),
-        neighbours: &[(PossibleIndices, Position)],
+        (tile, _): (u16, G::Position),
+        neighbours: &[(PossibleIndices, G::Direction)],
         _tiles: &[T],
-    ) -> bool {
+    ) -> Option<f32> {
         // for every neighbour, one possible tile must match with the current tile
-        neighbours.iter().all(|(nts, _)| {
-            nts.iter().any(|nt| {
-                self.allowed_neighbours.contains(&(tile, nt))
-                    || self.allowed_neighbours.contains(&(nt, tile))
+        neighbours
+            .iter()
+            .all(|(nts, _)| self.allowed_neighbours.contains_any(tile as usize, *nts))
+            .then_some(1.0)
+    }
+
+    fn as_directional_neighbours(
+        &self,
+        _tiles: &[T],
+    ) -> Option<DirectionalNeighbours<G::Direction>> {
+        let directions = <G::Direction as NeighbourDirection>::ALL;
+        let mut allowed = TileMasks::new(self.num_tiles * directions.len(), self.num_tiles);
+
+        // the neighbours of a tile are allowed in every direction
+        for tile in 0..self.num_tiles as u16 {
+            for dir in directions {
+                allowed.set_mask(
+                    DirectionalNeighbours::mask_index(tile, *dir),
+                    self.allowed_neighbours.mask(tile as usize),
+                );
+            }
+        }
+
+        Some(DirectionalNeighbours::from_masks(allowed))
+    }
+}
+
+/// A [Constraint] which defines what tiles can be neighboured to each other in a specific [Direction].
+///
+/// A rule `(a, Direction::Up, b)` means that b can be the upper neighbour of a. This implies that a
+/// can be the lower neighbour of b, so the opposite rule is added automatically.
+///
+/// The rules use the [NeighbourDirection] of the [Topology], for example a
+/// [crate::direction::HexDirection] on a [crate::topology::HexGrid].
+#[derive(Clone)]
+pub struct DirectionalNeighbours<D = Direction> {
+    /// For every tile and direction, the mask of the tiles which are allowed as neighbour
+    /// in this direction. See [DirectionalNeighbours::mask_index] for the layout.
+    allowed_neighbours: TileMasks,
+    _direction: PhantomData<D>,
+}
+
+impl<D: NeighbourDirection> DirectionalNeighbours<D> {
+    pub fn new<T: PartialEq>(
+        allowed_neighbours: impl IntoIterator<Item = (T, D, T)>,
+        all_tiles: &[T],
+    ) -> Self {
+        Self::from_index_rules(
+            all_tiles.len(),
+            allowed_neighbours
+                .into_iter()
+                .map(|(t0, dir, t1)| (tile_index(all_tiles, t0), dir, tile_index(all_tiles, t1))),
+        )
+    }
+
+    /// Create the [DirectionalNeighbours] from the [Sockets] of all tiles. Two tiles can be
+    /// neighbours in a direction if their facing sockets fit together.
+    pub fn from_sockets<T: Sockets<D>>(all_tiles: &[T]) -> Self {
+        Self::from_socket_fn(all_tiles, |tile, dir| tile.socket(dir))
+    }
+
+    /// Create the [DirectionalNeighbours] from the sockets returned by the given function for every tile and
+    /// direction. This is an alternative to [DirectionalNeighbours::from_sockets] for tiles which cannot
+    /// implement [Sockets].
+    pub fn from_socket_fn<T, L: PartialEq>(
+        all_tiles: &[T],
+        socket: impl Fn(&T, D) -> Socket<L>,
+    ) -> Self {
+        Self::from_fn(all_tiles.len(), |t0, dir, t1| {
+            socket(&all_tiles[t0 as usize], dir).fits(&socket(&all_tiles[t1 as usize], dir.opposite()))
+        })
+    }
+
+    /// Create the [DirectionalNeighbours] from rules which already use the tile indices.
+    pub(crate) fn from_index_rules(
+        num_tiles: usize,
+        allowed_neighbours: impl IntoIterator<Item = (u16, D, u16)>,
+    ) -> Self {
+        let mut allowed = TileMasks::new(num_tiles * D::ALL.len(), num_tiles);
+
+        for (t0, dir, t1) in allowed_neighbours {
+            allowed.insert(Self::mask_index(t0, dir), t1);
+            allowed.insert(Self::mask_index(t1, dir.opposite()), t0);
+        }
+
+        Self::from_masks(allowed)
+    }
+
+    /// Create the [DirectionalNeighbours] by asking the given function for every pair of tile indices
+    /// and direction if the second tile can be the neighbour of the first one in this direction.
+    pub(crate) fn from_fn(
+        num_tiles: usize,
+        allowed: impl Fn(u16, D, u16) -> bool,
+    ) -> Self {
+        let mut masks = TileMasks::new(num_tiles * D::ALL.len(), num_tiles);
+
+        for t0 in 0..num_tiles as u16 {
+            for dir in D::ALL.iter().copied() {
+                for t1 in 0..num_tiles as u16 {
+                    if allowed(t0, dir, t1) {
+                        masks.insert(Self::mask_index(t0, dir), t1);
+                        masks.insert(Self::mask_index(t1, dir.opposite()), t0);
+                    }
+                }
+            }
+        }
+
+        Self::from_masks(masks)
+    }
+
+    /// Create the [DirectionalNeighbours] from a mask for every tile and direction, in the layout
+    /// of [DirectionalNeighbours::mask_index].
+    fn from_masks(allowed_neighbours: TileMasks) -> Self {
+        DirectionalNeighbours {
+            allowed_neighbours,
+            _direction: PhantomData,
+        }
+    }
+
+    /// Return the index of the mask of the given tile and direction, which is the same as
+    /// the one of the [AdjacencyPropagator].
+    fn mask_index(
+        tile: u16,
+        direction: D,
+    ) -> usize {
+        AdjacencyPropagator::<D>::index(tile, direction)
+    }
+
+    /// Tells if the neighbour tile is allowed in the given direction of the tile.
+    pub fn allowed(
+        &self,
+        tile: u16,
+        direction: D,
+        neighbour: u16,
+    ) -> bool {
+        self.allowed_neighbours
+            .contains(Self::mask_index(tile, direction), neighbour)
+    }
+
+    /// Return the mask of the tiles which are allowed as neighbour in the given direction of the tile.
+    pub(crate) fn allowed_mask(
+        &self,
+        tile: u16,
+        direction: D,
+    ) -> &[u128] {
+        self.allowed_neighbours
+            .mask(Self::mask_index(tile, direction))
+    }
+}
+
+impl DirectionalNeighbours {
+    /// Create the [DirectionalNeighbours] for tiles which were expanded with [Oriented::expand].
+    ///
+    /// Every given rule is applied to all rotations and reflections, so the rules only have to be
+    /// provided for a single orientation of the tiles.
+    pub fn new_with_symmetry<T, R>(
+        allowed_neighbours: impl IntoIterator<Item = (R, Direction, R)>,
+        all_tiles: &[Oriented<T>],
+    ) -> Self
+    where
+        T: Symmetric + Clone + PartialEq,
+        R: Into<Oriented<T>>,
+    {
+        let allowed_neighbours = allowed_neighbours
+            .into_iter()
+            .flat_map(|(t0, dir, t1)| {
+                let (t0, t1): (Oriented<T>, Oriented<T>) = (t0.into(), t1.into());
+
+                Orientation::ALL
+                    .into_iter()
+                    .map(move |o| (t0.transformed(o), o.apply(dir), t1.transformed(o)))
             })
+            .collect::<Vec<_>>();
+
+        Self::new(allowed_neighbours, all_tiles)
+    }
+
+    /// Learn the [DirectionalNeighbours] from a sample. A tile can be the neighbour of another tile
+    /// in a direction if it is found in this direction of the tile somewhere in the sample.
+    pub fn from_sample<T: Clone + PartialEq>(sample: &WfcGrid<T>) -> SampleRules<T, Self> {
+        SampleRules::learn(sample, |num_tiles, neighbour_counts| {
+            Self::from_index_rules(
+                num_tiles,
+                neighbour_counts
+                    .iter()
+                    .map(|(t0, dir, t1, _)| (*t0, *dir, *t1)),
+            )
         })
     }
 }
+
+impl<T, D, G> Constraint<T, G> for DirectionalNeighbours<D>
+where
+    D: NeighbourDirection,
+    G: Topology<Direction = D>,
+{
+    fn valid(
+        &self,
+        (tile, _): (u16, G::Position),
+        neighbours: &[(PossibleIndices, D)],
+        _tiles: &[T],
+    ) -> Option<f32> {
+        // for every neighbour, one possible tile must be allowed in the direction of the neighbour
+        neighbours
+            .iter()
+            .all(|(nts, dir)| {
+                self.allowed_neighbours
+                    .contains_any(Self::mask_index(tile, *dir), *nts)
+            })
+            .then_some(1.0)
+    }
+
+    fn as_directional_neighbours(
+        &self,
+        _tiles: &[T],
+    ) -> Option<DirectionalNeighbours<D>> {
+        Some(self.clone())
+    }
+}
+
+/// A [Constraint] which changes the weight of a tile depending on its neighbours, without
+/// making any tile impossible.
+///
+/// A bias `(a, b, modifier)` means that the weight of a gets multiplied with the modifier for every
+/// neighbour which is collapsed to b. A modifier above 1.0 makes a more likely to be next to b, a
+/// modifier below 1.0 makes it less likely.
+#[derive(Clone)]
+pub struct NeighbourBias {
+    biases: Vec<(u16, u16, f32)>,
+}
+
+impl NeighbourBias {
+    pub fn new<T: PartialEq>(
+        biases: impl IntoIterator<Item = (T, T, f32)>,
+        all_tiles: &[T],
+    ) -> Self {
+        NeighbourBias {
+            biases: biases
+                .into_iter()
+                .map(|(t0, t1, modifier)| {
+                    (
+                        tile_index(all_tiles, t0),
+                        tile_index(all_tiles, t1),
+                        modifier,
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<T, G: Topology> Constraint<T, G> for NeighbourBias {
+    fn valid(
+        &self,
+        (tile, _): (u16, G::Position),
+        neighbours: &[(PossibleIndices, G::Direction)],
+        _tiles: &[T],
+    ) -> Option<f32> {
+        let modifier = neighbours
+            .iter()
+            .filter(|(nts, _)| nts.entropy() == 1)
+            .map(|(nts, _)| nts.get(0))
+            .flat_map(|nt| {
+                self.biases
+                    .iter()
+                    .filter(move |(t0, t1, _)| *t0 == tile && *t1 == nt)
+            })
+            .map(|(_, _, modifier)| *modifier)
+            .product();
+
+        Some(modifier)
+    }
+}
+
+/// Return the index of the given tile in all tiles, which the constraints use instead of the tile itself.
+fn tile_index<T: PartialEq>(
+    all_tiles: &[T],
+    tile: T,
+) -> u16 {
+    all_tiles
+        .iter()
+        .position(|t| *t == tile)
+        .expect("The tile should be in the possible tiles") as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cell::{BitCell, Cell, DynCell},
+        constraints::{Constraint, DirectionalNeighbours, PossibleNeighbours},
+        direction::Direction,
+    };
+    use pad::p;
+
+    #[test]
+    fn possible_neighbours_check_bits_and_arrays() {
+        let tiles = ['a', 'b', 'c'];
+        let neighbours = PossibleNeighbours::new([('a', 'b')], &tiles);
+
+        assert!(neighbours.allowed(1, 0));
+        assert!(!neighbours.allowed(0, 2));
+
+        let mut bit_cell = BitCell::new(tiles.len());
+        bit_cell.set_indices([1, 2]);
+        let mut dyn_cell = DynCell::new(tiles.len());
+        dyn_cell.set_indices([1, 2]);
+
+        for indices in [bit_cell.get_possible_indices(), dyn_cell.get_possible_indices()] {
+            let valid = |tile| {
+                <PossibleNeighbours as Constraint<char>>::valid(
+                    &neighbours,
+                    (tile, p!(0, 0)),
+                    &[(indices, Direction::Right)],
+                    &tiles,
+                )
+            };
+
+            assert_eq!(valid(0), Some(1.0));
+            assert_eq!(valid(2), None);
+        }
+    }
+
+    #[test]
+    fn directional_neighbours_imply_opposite_rules() {
+        let tiles = ['a', 'b', 'c'];
+        let neighbours = DirectionalNeighbours::new([('a', Direction::Up, 'b')], &tiles);
+
+        assert!(neighbours.allowed(0, Direction::Up, 1));
+        assert!(neighbours.allowed(1, Direction::Down, 0));
+        assert!(!neighbours.allowed(0, Direction::Down, 1));
+        assert!(!neighbours.allowed(1, Direction::Up, 0));
+
+        let mut cell = BitCell::new(tiles.len());
+        cell.set_indices([1]);
+        let valid = |tile, dir| {
+            <DirectionalNeighbours as Constraint<char>>::valid(
+                &neighbours,
+                (tile, p!(0, 0)),
+                &[(cell.get_possible_indices(), dir)],
+                &tiles,
+            )
+        };
+
+        // b can be above a, but not below it
+        assert_eq!(valid(0, Direction::Up), Some(1.0));
+        assert_eq!(valid(0, Direction::Down), None);
+        assert_eq!(valid(2, Direction::Up), None);
+    }
+}
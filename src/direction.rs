@@ -0,0 +1,233 @@
+use std::fmt::Debug;
+
+/// The direction from a cell to one of its neighbours, which labels the edge between them in a
+/// [crate::topology::Topology]. Directional constraints decide for every direction separately which tiles
+/// can be neighbours.
+pub trait NeighbourDirection: Copy + Debug + Eq + 'static {
+    /// All directions of this type, in the order of [NeighbourDirection::index].
+    const ALL: &'static [Self];
+
+    /// Return the index of this direction in [NeighbourDirection::ALL].
+    fn index(self) -> usize;
+
+    /// Return the direction pointing the other way, which leads from the neighbour back to the cell.
+    fn opposite(self) -> Self;
+}
+
+/// The direction from a position to one of its cardinal neighbours.
+/// Up points to the neighbour with the lower y coordinate, Left to the neighbour with the lower x coordinate.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// All [Direction]s.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    /// Return the [Direction] pointing the other way.
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    /// Return this [Direction] rotated clockwise by 90 degrees.
+    pub fn rotated_clockwise(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    /// Return this [Direction] mirrored on the vertical axis, so left and right are swapped.
+    pub fn mirrored(self) -> Direction {
+        match self {
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            dir => dir,
+        }
+    }
+
+    /// Return the offset on the x and y axis to move one step in this [Direction].
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
+}
+
+impl NeighbourDirection for Direction {
+    const ALL: &'static [Direction] = &Direction::ALL;
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn opposite(self) -> Direction {
+        Direction::opposite(self)
+    }
+}
+
+/// The direction from a hexagon to one of its six neighbours on a [crate::topology::HexGrid].
+/// The hexagons have pointy tops, so every hexagon has a left and right neighbour in the same row and
+/// two neighbours each in the rows above and below.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum HexDirection {
+    UpLeft,
+    UpRight,
+    Left,
+    Right,
+    DownLeft,
+    DownRight,
+}
+
+impl HexDirection {
+    /// All [HexDirection]s.
+    pub const ALL: [HexDirection; 6] = [
+        HexDirection::UpLeft,
+        HexDirection::UpRight,
+        HexDirection::Left,
+        HexDirection::Right,
+        HexDirection::DownLeft,
+        HexDirection::DownRight,
+    ];
+
+    /// Return the [HexDirection] pointing the other way.
+    pub fn opposite(self) -> HexDirection {
+        match self {
+            HexDirection::UpLeft => HexDirection::DownRight,
+            HexDirection::UpRight => HexDirection::DownLeft,
+            HexDirection::Left => HexDirection::Right,
+            HexDirection::Right => HexDirection::Left,
+            HexDirection::DownLeft => HexDirection::UpRight,
+            HexDirection::DownRight => HexDirection::UpLeft,
+        }
+    }
+
+    /// Return the offset on the x and y axis to move one step in this [HexDirection] in offset coordinates.
+    /// As the odd rows are shifted to the right, the offset depends on the row the step starts in.
+    pub fn offset(
+        self,
+        odd_row: bool,
+    ) -> (isize, isize) {
+        // the diagonal neighbours of an odd row are shifted one column to the right
+        let shift = odd_row as isize;
+
+        match self {
+            HexDirection::UpLeft => (shift - 1, -1),
+            HexDirection::UpRight => (shift, -1),
+            HexDirection::Left => (-1, 0),
+            HexDirection::Right => (1, 0),
+            HexDirection::DownLeft => (shift - 1, 1),
+            HexDirection::DownRight => (shift, 1),
+        }
+    }
+}
+
+impl NeighbourDirection for HexDirection {
+    const ALL: &'static [HexDirection] = &HexDirection::ALL;
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn opposite(self) -> HexDirection {
+        HexDirection::opposite(self)
+    }
+}
+
+/// The direction from a voxel to one of its six face neighbours on a [crate::topology::VoxelGrid].
+///
+/// Up, Down, Left and Right point to the neighbours in the same layer, just like the 2D [Direction].
+/// Above points to the neighbour in the layer with the higher z coordinate, Below to the one with the lower.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum VoxelDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+    Above,
+    Below,
+}
+
+impl VoxelDirection {
+    /// All [VoxelDirection]s.
+    pub const ALL: [VoxelDirection; 6] = [
+        VoxelDirection::Up,
+        VoxelDirection::Down,
+        VoxelDirection::Left,
+        VoxelDirection::Right,
+        VoxelDirection::Above,
+        VoxelDirection::Below,
+    ];
+
+    /// Return the [VoxelDirection] pointing the other way.
+    pub fn opposite(self) -> VoxelDirection {
+        match self {
+            VoxelDirection::Up => VoxelDirection::Down,
+            VoxelDirection::Down => VoxelDirection::Up,
+            VoxelDirection::Left => VoxelDirection::Right,
+            VoxelDirection::Right => VoxelDirection::Left,
+            VoxelDirection::Above => VoxelDirection::Below,
+            VoxelDirection::Below => VoxelDirection::Above,
+        }
+    }
+
+    /// Return the offset on the x, y and z axis to move one step in this [VoxelDirection].
+    pub fn offset(self) -> (isize, isize, isize) {
+        match self {
+            VoxelDirection::Up => (0, -1, 0),
+            VoxelDirection::Down => (0, 1, 0),
+            VoxelDirection::Left => (-1, 0, 0),
+            VoxelDirection::Right => (1, 0, 0),
+            VoxelDirection::Above => (0, 0, 1),
+            VoxelDirection::Below => (0, 0, -1),
+        }
+    }
+}
+
+impl NeighbourDirection for VoxelDirection {
+    const ALL: &'static [VoxelDirection] = &VoxelDirection::ALL;
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn opposite(self) -> VoxelDirection {
+        VoxelDirection::opposite(self)
+    }
+}
+
+/// The only direction of a [crate::topology::Graph] whose edges have no direction, like the borders between
+/// the regions of a map. All neighbours of a cell lie in this direction, so a cell can have any amount of them.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Undirected;
+
+impl NeighbourDirection for Undirected {
+    const ALL: &'static [Undirected] = &[Undirected];
+
+    fn index(self) -> usize {
+        0
+    }
+
+    fn opposite(self) -> Undirected {
+        self
+    }
+}
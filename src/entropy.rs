@@ -0,0 +1,231 @@
+use crate::heuristic::EntropyHeuristic;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// The sums which are needed to calculate the Shannon entropy of a cell. They can be
+/// updated tile by tile, so the entropy does not have to be recalculated from scratch.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct CellEntropy {
+    sum_of_weights: f32,
+    sum_of_weight_log_weights: f32,
+}
+
+impl CellEntropy {
+    pub fn from_weights(weights: impl IntoIterator<Item = f32>) -> Self {
+        let mut entropy = CellEntropy::default();
+
+        for weight in weights {
+            entropy.add(weight);
+        }
+
+        entropy
+    }
+
+    /// Add the weight of a possible tile.
+    pub fn add(
+        &mut self,
+        weight: f32,
+    ) {
+        // tiles with a weight of 0 never get chosen, so they don't add to the entropy
+        if weight > 0.0 {
+            self.sum_of_weights += weight;
+            self.sum_of_weight_log_weights += weight * weight.ln();
+        }
+    }
+
+    /// Remove the weight of a tile which is not possible anymore.
+    pub fn remove(
+        &mut self,
+        weight: f32,
+    ) {
+        if weight > 0.0 {
+            self.sum_of_weights -= weight;
+            self.sum_of_weight_log_weights -= weight * weight.ln();
+        }
+    }
+
+    pub fn entropy(&self) -> f32 {
+        if self.sum_of_weights <= 0.0 {
+            return 0.0;
+        }
+
+        // the incremental updates can drift slightly below zero, which no real entropy does
+        (self.sum_of_weights.ln() - self.sum_of_weight_log_weights / self.sum_of_weights).max(0.0)
+    }
+}
+
+/// A priority queue of the cells on the board, ordered by their entropy according to an
+/// [EntropyHeuristic]. This allows to find the cell with the lowest entropy without looking
+/// at every cell.
+///
+/// Entries are never updated. Instead, a cell gets pushed again whenever its entropy changes,
+/// and outdated entries are skipped by [EntropyQueue::prune]. The board rebuilds the queue with
+/// [EntropyQueue::rebuild] once the outdated entries pile up.
+#[derive(Clone, Debug)]
+pub(crate) struct EntropyQueue {
+    heuristic: EntropyHeuristic,
+    heap: BinaryHeap<QueueEntry>,
+}
+
+impl EntropyQueue {
+    pub fn new(heuristic: EntropyHeuristic) -> Self {
+        EntropyQueue {
+            heuristic,
+            heap: BinaryHeap::new(),
+        }
+    }
+
+    /// Return the [EntropyHeuristic] the cells are ordered by.
+    pub fn heuristic(&self) -> EntropyHeuristic {
+        self.heuristic
+    }
+
+    /// Return the amount of entries, including the outdated ones.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Replace all entries with the given cells, their entropy and their tie breaker. The existing
+    /// allocation is reused.
+    pub fn rebuild(
+        &mut self,
+        entries: impl IntoIterator<Item = (usize, f32, u32)>,
+    ) {
+        let mut heap = std::mem::take(&mut self.heap).into_vec();
+        heap.clear();
+        heap.extend(
+            entries
+                .into_iter()
+                .map(|(cell, entropy, tie_breaker)| QueueEntry {
+                    entropy,
+                    tie_breaker,
+                    cell,
+                }),
+        );
+
+        // building the heap at once is faster than pushing every entry
+        self.heap = BinaryHeap::from(heap);
+    }
+
+    pub fn push(
+        &mut self,
+        cell: usize,
+        entropy: f32,
+        tie_breaker: u32,
+    ) {
+        self.heap.push(QueueEntry {
+            entropy,
+            tie_breaker,
+            cell,
+        });
+    }
+
+    /// Remove outdated entries from the front of the queue, until the given function tells that
+    /// the entropy of the first entry is still the current one of its cell.
+    pub fn prune(
+        &mut self,
+        is_current: impl Fn(usize, f32) -> bool,
+    ) {
+        while let Some(entry) = self.heap.peek() {
+            if is_current(entry.cell, entry.entropy) {
+                return;
+            }
+
+            self.heap.pop();
+        }
+    }
+
+    /// Return the cell with the lowest entropy, assuming the queue was pruned.
+    pub fn peek(&self) -> Option<usize> {
+        self.heap.peek().map(|entry| entry.cell)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct QueueEntry {
+    entropy: f32,
+    tie_breaker: u32,
+    cell: usize,
+}
+
+impl PartialEq for QueueEntry {
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for QueueEntry {}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(
+        &self,
+        other: &Self,
+    ) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueueEntry {
+    fn cmp(
+        &self,
+        other: &Self,
+    ) -> Ordering {
+        // the BinaryHeap is a max-heap, so the order is reversed to get the lowest entropy first
+        other
+            .entropy
+            .total_cmp(&self.entropy)
+            .then_with(|| other.tie_breaker.cmp(&self.tie_breaker))
+            .then_with(|| other.cell.cmp(&self.cell))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        entropy::{CellEntropy, EntropyQueue},
+        heuristic::EntropyHeuristic,
+    };
+
+    #[test]
+    fn removing_weights_matches_shannon_entropy() {
+        let weights = [5.0, 0.5, 1.0, 0.0, 3.0, 20.0];
+        let mut entropy = CellEntropy::from_weights(weights);
+
+        // remove all but the last weight, as an empty cell has no meaningful entropy
+        for removed in 0..(weights.len() - 1) {
+            entropy.remove(weights[removed]);
+
+            let remaining = &weights[(removed + 1)..];
+            let sum = remaining.iter().sum::<f32>();
+            let expected = remaining
+                .iter()
+                .filter(|w| **w > 0.0)
+                .map(|w| -(w / sum) * (w / sum).ln())
+                .sum::<f32>();
+
+            assert!((entropy.entropy() - expected).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn queue_returns_lowest_current_entropy() {
+        let mut queue = EntropyQueue::new(EntropyHeuristic::RemainingTiles);
+        queue.push(0, 3.0, 0);
+        queue.push(1, 2.0, 5);
+        queue.push(2, 2.0, 1);
+
+        queue.prune(|_, _| true);
+        assert_eq!(queue.peek(), Some(2));
+
+        // the entry of cell 2 is outdated, for example because the cell was collapsed
+        queue.prune(|cell, _| cell != 2);
+        assert_eq!(queue.peek(), Some(1));
+
+        queue.rebuild([(0, 1.0, 0), (1, 2.0, 0)]);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.peek(), Some(0));
+    }
+}
@@ -0,0 +1,323 @@
+use crate::topology::VoxelPosition;
+use pad::p;
+use pad::position::Position;
+use std::ops::Index;
+
+/// The result of a [crate::Wfc], which holds the collapsed tile for every position of the board.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WfcGrid<T> {
+    width: usize,
+    height: usize,
+    /// The tiles in row-major order
+    tiles: Vec<T>,
+}
+
+impl<T> WfcGrid<T> {
+    /// Create a new [WfcGrid] from the given tiles, which must be in row-major order.
+    ///
+    /// Panics if the amount of tiles does not match the dimensions.
+    pub fn new(
+        width: usize,
+        height: usize,
+        tiles: Vec<T>,
+    ) -> Self {
+        assert_eq!(
+            width * height,
+            tiles.len(),
+            "The amount of tiles should match the dimensions of the grid"
+        );
+
+        WfcGrid {
+            width,
+            height,
+            tiles,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Return the tile at the given position, or None if the position is out of bounds.
+    pub fn get(
+        &self,
+        position: Position,
+    ) -> Option<&T> {
+        if position.x < 0
+            || position.y < 0
+            || position.x >= self.width as isize
+            || position.y >= self.height as isize
+        {
+            return None;
+        }
+
+        self.tiles
+            .get(self.width * position.y as usize + position.x as usize)
+    }
+
+    /// Return an [Iterator] over all positions and their tiles in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.tiles
+            .iter()
+            .enumerate()
+            .map(|(i, tile)| (p!(i % self.width, i / self.width), tile))
+    }
+
+    /// Return an [Iterator] over all rows of the grid, from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks panics with a size of 0, but an empty grid has no tiles anyway
+        self.tiles.chunks(self.width.max(1))
+    }
+
+    /// Return an [Iterator] over all columns of the grid, from left to right.
+    /// Every column iterates its tiles from top to bottom.
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.tiles.iter().skip(x).step_by(self.width))
+    }
+
+    /// Create a new [WfcGrid] with the same dimensions by applying the given function to every tile.
+    pub fn map<U>(
+        self,
+        f: impl FnMut(T) -> U,
+    ) -> WfcGrid<U> {
+        WfcGrid {
+            width: self.width,
+            height: self.height,
+            tiles: self.tiles.into_iter().map(f).collect(),
+        }
+    }
+
+    /// Convert the grid into a [Vec] of all positions and their tiles in row-major order.
+    pub fn into_vec(self) -> Vec<(Position, T)> {
+        let width = self.width;
+
+        self.tiles
+            .into_iter()
+            .enumerate()
+            .map(|(i, tile)| (p!(i % width, i / width), tile))
+            .collect()
+    }
+}
+
+impl<T> Index<Position> for WfcGrid<T> {
+    type Output = T;
+
+    fn index(
+        &self,
+        position: Position,
+    ) -> &Self::Output {
+        self.get(position)
+            .unwrap_or_else(|| panic!("The position {position:?} should be in the grid"))
+    }
+}
+
+impl<T> From<WfcGrid<T>> for Vec<(Position, T)> {
+    fn from(grid: WfcGrid<T>) -> Self {
+        grid.into_vec()
+    }
+}
+
+/// The result of a [crate::Wfc] on a [crate::topology::VoxelGrid], which holds the collapsed tile for every
+/// position of the board.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WfcVolume<T> {
+    width: usize,
+    height: usize,
+    depth: usize,
+    /// The tiles layer by layer, where every layer is in row-major order
+    tiles: Vec<T>,
+}
+
+impl<T> WfcVolume<T> {
+    /// Create a new [WfcVolume] from the given tiles, which must be ordered layer by layer, with every layer
+    /// in row-major order.
+    ///
+    /// Panics if the amount of tiles does not match the dimensions.
+    pub fn new(
+        width: usize,
+        height: usize,
+        depth: usize,
+        tiles: Vec<T>,
+    ) -> Self {
+        assert_eq!(
+            width * height * depth,
+            tiles.len(),
+            "The amount of tiles should match the dimensions of the volume"
+        );
+
+        WfcVolume {
+            width,
+            height,
+            depth,
+            tiles,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Return the tile at the given position, or None if the position is out of bounds.
+    pub fn get(
+        &self,
+        position: VoxelPosition,
+    ) -> Option<&T> {
+        if position.x < 0
+            || position.y < 0
+            || position.z < 0
+            || position.x >= self.width as isize
+            || position.y >= self.height as isize
+            || position.z >= self.depth as isize
+        {
+            return None;
+        }
+
+        let layer = self.width * self.height * position.z as usize;
+        self.tiles
+            .get(layer + self.width * position.y as usize + position.x as usize)
+    }
+
+    /// Return an [Iterator] over all positions and their tiles, layer by layer.
+    pub fn iter(&self) -> impl Iterator<Item = (VoxelPosition, &T)> {
+        self.tiles
+            .iter()
+            .enumerate()
+            .map(|(i, tile)| (self.position(i), tile))
+    }
+
+    /// Return the layer with the given z coordinate as [WfcGrid], or None if it is out of bounds.
+    pub fn layer(
+        &self,
+        z: usize,
+    ) -> Option<WfcGrid<&T>> {
+        if z >= self.depth {
+            return None;
+        }
+
+        let layer_size = self.width * self.height;
+        let tiles = self.tiles[(z * layer_size)..((z + 1) * layer_size)]
+            .iter()
+            .collect();
+
+        Some(WfcGrid::new(self.width, self.height, tiles))
+    }
+
+    /// Create a new [WfcVolume] with the same dimensions by applying the given function to every tile.
+    pub fn map<U>(
+        self,
+        f: impl FnMut(T) -> U,
+    ) -> WfcVolume<U> {
+        WfcVolume {
+            width: self.width,
+            height: self.height,
+            depth: self.depth,
+            tiles: self.tiles.into_iter().map(f).collect(),
+        }
+    }
+
+    /// Convert the volume into a [Vec] of all positions and their tiles, layer by layer.
+    pub fn into_vec(self) -> Vec<(VoxelPosition, T)> {
+        let positions = (0..self.tiles.len())
+            .map(|i| self.position(i))
+            .collect::<Vec<_>>();
+
+        positions.into_iter().zip(self.tiles).collect()
+    }
+
+    /// Return the position of the tile with the given index.
+    fn position(
+        &self,
+        index: usize,
+    ) -> VoxelPosition {
+        let layer_size = self.width * self.height;
+        let (z, index) = (index / layer_size, index % layer_size);
+
+        VoxelPosition::new(
+            (index % self.width) as isize,
+            (index / self.width) as isize,
+            z as isize,
+        )
+    }
+}
+
+impl<T> Index<VoxelPosition> for WfcVolume<T> {
+    type Output = T;
+
+    fn index(
+        &self,
+        position: VoxelPosition,
+    ) -> &Self::Output {
+        self.get(position)
+            .unwrap_or_else(|| panic!("The position {position:?} should be in the volume"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        grid::{WfcGrid, WfcVolume},
+        topology::VoxelPosition,
+    };
+    use pad::p;
+
+    #[test]
+    fn grid_access_works() {
+        let grid = WfcGrid::new(3, 2, vec![0, 1, 2, 3, 4, 5]);
+
+        assert_eq!(grid.get(p!(2, 1)), Some(&5));
+        assert_eq!(grid.get(p!(3, 0)), None);
+        assert_eq!(grid.get(p!(-1, 0)), None);
+        assert_eq!(grid[p!(1, 1)], 4);
+
+        let rows = grid.rows().collect::<Vec<_>>();
+        assert_eq!(rows, vec![&[0, 1, 2][..], &[3, 4, 5][..]]);
+
+        let columns = grid
+            .columns()
+            .map(|c| c.copied().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(columns, vec![vec![0, 3], vec![1, 4], vec![2, 5]]);
+
+        assert_eq!(
+            grid.into_vec(),
+            vec![
+                (p!(0, 0), 0),
+                (p!(1, 0), 1),
+                (p!(2, 0), 2),
+                (p!(0, 1), 3),
+                (p!(1, 1), 4),
+                (p!(2, 1), 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn volume_access_works() {
+        let volume = WfcVolume::new(2, 1, 3, vec![0, 1, 2, 3, 4, 5]);
+
+        assert_eq!(volume.get(VoxelPosition::new(1, 0, 2)), Some(&5));
+        assert_eq!(volume.get(VoxelPosition::new(0, 1, 0)), None);
+        assert_eq!(volume[VoxelPosition::new(0, 0, 1)], 2);
+
+        let layer = volume.layer(1).unwrap();
+        assert_eq!(layer[p!(1, 0)], &3);
+        assert!(volume.layer(3).is_none());
+
+        assert_eq!(
+            volume.into_vec()[3],
+            (VoxelPosition::new(1, 0, 1), 3)
+        );
+    }
+}
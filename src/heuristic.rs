@@ -0,0 +1,201 @@
+use crate::{
+    board::BoardView,
+    cell::Cell,
+    topology::{SquareGrid, Topology},
+};
+use pad::position::Position;
+
+/// Decides which cell of the board gets collapsed next. The choice has a big influence on the style
+/// of the result and on how often the wfc runs into contradictions.
+///
+/// Besides the built-in heuristics, every closure `FnMut(BoardView<C, G>) -> G::Position` is a [SelectionHeuristic].
+pub trait SelectionHeuristic<C: Cell, G: Topology = SquareGrid> {
+    /// Return the position of the cell which gets collapsed next. This must be one of the
+    /// [BoardView::non_collapsed_positions], of which there is always at least one.
+    fn select(
+        &mut self,
+        board: BoardView<'_, C, G>,
+    ) -> G::Position;
+
+    /// Return the [EntropyHeuristic] which this heuristic passes to [BoardView::min_entropy_position], if any.
+    /// The board then keeps the positions ordered by this entropy, which makes the lookup a lot faster.
+    fn entropy_heuristic(&self) -> Option<EntropyHeuristic> {
+        None
+    }
+}
+
+impl<C: Cell, G: Topology, F> SelectionHeuristic<C, G> for F
+where
+    F: FnMut(BoardView<'_, C, G>) -> G::Position,
+{
+    fn select(
+        &mut self,
+        board: BoardView<'_, C, G>,
+    ) -> G::Position {
+        self(board)
+    }
+}
+
+/// Selects the cell with the lowest entropy. Ties between cells with the same entropy are broken randomly.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum EntropyHeuristic {
+    /// The entropy is the amount of tiles which are still possible in a cell, which is also known as the
+    /// minimum remaining values heuristic. The weights of the tiles are ignored.
+    #[default]
+    RemainingTiles,
+    /// The entropy is the weighted Shannon entropy of the possible tiles in a cell, like in the
+    /// reference implementation of the wfc. This respects the weights of the tiles, which gives
+    /// better results if they are very different.
+    Shannon,
+}
+
+impl<C: Cell, G: Topology> SelectionHeuristic<C, G> for EntropyHeuristic {
+    fn select(
+        &mut self,
+        board: BoardView<'_, C, G>,
+    ) -> G::Position {
+        board.min_entropy_position(*self)
+    }
+
+    fn entropy_heuristic(&self) -> Option<EntropyHeuristic> {
+        Some(*self)
+    }
+}
+
+/// Selects the cells row by row from top to bottom, and every row from left to right.
+///
+/// On other topologies, the cells are selected in the order of their indices.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Scanline;
+
+impl<C: Cell, G: Topology> SelectionHeuristic<C, G> for Scanline {
+    fn select(
+        &mut self,
+        board: BoardView<'_, C, G>,
+    ) -> G::Position {
+        // the positions are ordered by their cell index, which is row-major on grids
+        board
+            .non_collapsed_positions()
+            .next()
+            .expect("At least one non collapsed cell should exist")
+    }
+}
+
+/// Selects the cells in a clockwise spiral around the given center, so the result grows outward from it.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Spiral {
+    pub center: Position,
+}
+
+impl Spiral {
+    pub fn new(center: Position) -> Self {
+        Spiral { center }
+    }
+
+    /// Return the ring around the center the given position is on, and the index of the position on
+    /// this ring. Every ring starts at its upper left corner.
+    fn spiral_index(
+        &self,
+        position: Position,
+    ) -> (isize, isize) {
+        let dx = position.x - self.center.x;
+        let dy = position.y - self.center.y;
+        let ring = dx.abs().max(dy.abs());
+
+        let index = if dy == -ring && dx < ring {
+            // upper edge, from left to right
+            dx + ring
+        } else if dx == ring && dy < ring {
+            // right edge, from top to bottom
+            2 * ring + dy + ring
+        } else if dy == ring && dx > -ring {
+            // lower edge, from right to left
+            4 * ring + ring - dx
+        } else {
+            // left edge, from bottom to top
+            6 * ring + ring - dy
+        };
+
+        (ring, index)
+    }
+}
+
+impl<C: Cell, G: Topology<Position = Position>> SelectionHeuristic<C, G> for Spiral {
+    fn select(
+        &mut self,
+        board: BoardView<'_, C, G>,
+    ) -> Position {
+        board
+            .non_collapsed_positions()
+            .min_by_key(|pos| self.spiral_index(*pos))
+            .expect("At least one non collapsed cell should exist")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        WfcBuilder,
+        cell::BitCell,
+        constraints::DirectionalNeighbours,
+        direction::Direction,
+        heuristic::{EntropyHeuristic, Spiral},
+    };
+    use pad::p;
+
+    #[test]
+    fn shannon_entropy_respects_weights() {
+        // the tile 2 can't have a right neighbour, so only the last cell has three possible tiles
+        let tiles = vec![0, 1, 2];
+        let rules = DirectionalNeighbours::new(
+            (0..2).flat_map(|t0| (0..3).map(move |t1| (t0, Direction::Right, t1))),
+            &tiles,
+        );
+
+        for seed in 0..10 {
+            let first_position = |heuristic| {
+                WfcBuilder::<u8, BitCell>::new(3, 1, tiles.clone())
+                    .with_constraint(rules.clone())
+                    .with_weight_map(|pos, index| match (pos.x, index) {
+                        (2, 0) => 100.0,
+                        _ => 1.0,
+                    })
+                    .with_heuristic(heuristic)
+                    .with_seed(seed)
+                    .build()
+                    .unwrap()
+                    .step()
+                    .unwrap()
+                    .unwrap()
+                    .position
+            };
+
+            // the last cell has the most tiles, but is nearly certain to get the heavy tile 0
+            assert_ne!(first_position(EntropyHeuristic::RemainingTiles), p!(2, 0));
+            assert_eq!(first_position(EntropyHeuristic::Shannon), p!(2, 0));
+        }
+    }
+
+    #[test]
+    fn spiral_goes_around_center() {
+        let spiral = Spiral::new(p!(1, 1));
+
+        let mut positions = p!(0, 0).iter_to(p!(2, 2)).collect::<Vec<_>>();
+        positions.sort_by_key(|pos| spiral.spiral_index(*pos));
+
+        assert_eq!(
+            positions,
+            vec![
+                p!(1, 1),
+                p!(0, 0),
+                p!(1, 0),
+                p!(2, 0),
+                p!(2, 1),
+                p!(2, 2),
+                p!(1, 2),
+                p!(0, 2),
+                p!(0, 1),
+            ]
+        );
+    }
+}
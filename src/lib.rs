@@ -1,35 +1,64 @@
 use crate::{
-    board::Board,
+    backtracking::Backtracker,
+    board::{Board, BoardSnapshot},
     cell::{Cell, PossibleIndices},
     constraints::{Constraint, TileConstraints},
+    heuristic::{EntropyHeuristic, SelectionHeuristic},
+    observer::{Observer, Observers},
     random::Random,
+    symmetry::{Oriented, Symmetric},
+    topology::{HexGrid, Masked, SquareGrid, Topology, VoxelGrid},
 };
+pub use backtracking::Backtracking;
+pub use board::BoardView;
+pub use grid::{WfcGrid, WfcVolume};
 use pad::position::Position;
+pub use step::{Step, Steps};
 use std::{
     error::Error,
-    fmt::{Display, Formatter},
+    fmt::{Debug, Display, Formatter},
     hash::Hash,
     marker::PhantomData,
 };
 
+mod backtracking;
+mod bitmask;
 mod board;
 pub mod cell;
 pub mod constraints;
+pub mod direction;
+mod entropy;
+mod grid;
+pub mod heuristic;
+pub mod observer;
+pub mod overlapping;
+mod propagator;
 mod random;
+pub mod sample;
+pub mod sockets;
+mod step;
+pub mod symmetry;
+pub mod topology;
 
-// todo
-//  - Edge Colors, so no neighbour constraints have to be set manually
-//  - rotating tiles (this might be possible using a trait)
-//  - MAYBE Providing an iterator, so I can watch the wfc work
+/// A function which returns a weight for a tile index at a position, see [WfcBuilder::with_weight_map].
+type WeightMap<P> = Box<dyn Fn(P, usize) -> f32>;
 
 /// The builder for a [Wfc].
-pub struct WfcBuilder<T: Clone, C: Cell> {
-    width: usize,
-    height: usize,
+///
+/// The board is a [SquareGrid] by default. Other shapes can be created with [WfcBuilder::from_topology],
+/// or cut out of a topology with [WfcBuilder::with_mask].
+pub struct WfcBuilder<T: Clone, C: Cell, G: Topology = SquareGrid> {
+    topology: G,
     tiles: Vec<T>,
-    tile_constraints: TileConstraints<T>,
+    tile_constraints: TileConstraints<T, G>,
     random: Random,
     weights: Vec<f32>,
+    weight_map: Option<WeightMap<G::Position>>,
+    /// The [SelectionHeuristic], or None for the default [EntropyHeuristic]
+    heuristic: Option<Box<dyn SelectionHeuristic<C, G>>>,
+    backtracking: Option<Backtracking>,
+    retries: usize,
+    observers: Observers<T, G::Position>,
     _cell_type: PhantomData<C>,
 }
 
@@ -41,14 +70,117 @@ where
         width: usize,
         height: usize,
         tiles: Vec<T>,
+    ) -> Self {
+        Self::from_topology(SquareGrid::new(width, height), tiles)
+    }
+
+    /// Set if the board wraps around on the x axis (the left and right edges are neighbours) and on
+    /// the y axis (the top and bottom edges are neighbours), see [SquareGrid::with_wrapping].
+    ///
+    /// The constraints are then respected across the wrapping edges, so the result can be tiled seamlessly.
+    pub fn with_wrapping(
+        mut self,
+        wrap_x: bool,
+        wrap_y: bool,
+    ) -> Self {
+        self.topology = self.topology.with_wrapping(wrap_x, wrap_y);
+        self
+    }
+}
+
+impl<T, C: Cell> WfcBuilder<T, C, HexGrid>
+where
+    T: Clone,
+{
+    /// Create a [WfcBuilder] for a [HexGrid] with the given dimensions. Directional constraints use
+    /// [direction::HexDirection]s, and the result is returned in the offset coordinates of the grid.
+    pub fn new_hex(
+        width: usize,
+        height: usize,
+        tiles: Vec<T>,
+    ) -> Self {
+        Self::from_topology(HexGrid::new(width, height), tiles)
+    }
+
+    /// Set if the board wraps around on the x axis and on the y axis, see [HexGrid::with_wrapping].
+    ///
+    /// If the board wraps on the y axis, but has an odd height, [WfcBuilder::build] fails.
+    pub fn with_wrapping(
+        mut self,
+        wrap_x: bool,
+        wrap_y: bool,
+    ) -> Self {
+        self.topology = self.topology.with_wrapping(wrap_x, wrap_y);
+        self
+    }
+}
+
+impl<T, C: Cell> WfcBuilder<T, C, VoxelGrid>
+where
+    T: Clone,
+{
+    /// Create a [WfcBuilder] for a 3D [VoxelGrid] with `depth` layers of the given width and height.
+    /// Directional constraints use [direction::VoxelDirection]s, which include the neighbours in the
+    /// layers above and below, and the result is a [WfcVolume].
+    pub fn new_3d(
+        width: usize,
+        height: usize,
+        depth: usize,
+        tiles: Vec<T>,
+    ) -> Self {
+        Self::from_topology(VoxelGrid::new(width, height, depth), tiles)
+    }
+}
+
+impl<T, C: Cell, G: Topology> WfcBuilder<T, C, G>
+where
+    T: Clone,
+{
+    /// Create a [WfcBuilder] for a board with the given [Topology].
+    pub fn from_topology(
+        topology: G,
+        tiles: Vec<T>,
     ) -> Self {
         WfcBuilder {
-            width,
-            height,
+            topology,
             weights: vec![1.0; tiles.len()],
+            weight_map: None,
+            heuristic: None,
             tiles,
             tile_constraints: TileConstraints::default(),
             random: Random::new(),
+            backtracking: None,
+            retries: 0,
+            observers: Observers::default(),
+            _cell_type: PhantomData,
+        }
+    }
+
+    /// Exclude every position from the board for which the given mask returns false, so the board can have
+    /// any shape and even holes. The board becomes [Masked]: no cells exist at the excluded positions, their
+    /// neighbours treat them as absent, and the result holds None for them.
+    ///
+    /// A [SelectionHeuristic] is made for a specific topology, so one which was set before the mask is
+    /// reset to the default. Call [WfcBuilder::with_heuristic] after this to set one for the masked board.
+    pub fn with_mask(
+        self,
+        included: impl Fn(G::Position) -> bool,
+    ) -> WfcBuilder<T, C, Masked<G>>
+    where
+        T: 'static,
+        G: 'static,
+    {
+        WfcBuilder {
+            topology: Masked::new(self.topology, included),
+            tiles: self.tiles,
+            tile_constraints: self.tile_constraints.masked(),
+            random: self.random,
+            weights: self.weights,
+            weight_map: self.weight_map,
+            heuristic: None,
+            backtracking: self.backtracking,
+            retries: self.retries,
+            observers: self.observers,
             _cell_type: PhantomData,
         }
     }
@@ -75,71 +207,407 @@ where
         self
     }
 
+    /// Set a function which returns a weight for a tile index at a specific position. The weight of the
+    /// tile in that cell is its weight from [WfcBuilder::with_weights] multiplied with the returned weight.
+    ///
+    /// This allows to change the distribution of the tiles over the board, for example by using a noise map
+    /// or a heightmap. The constraints are still respected everywhere.
+    pub fn with_weight_map(
+        mut self,
+        weight_map: impl Fn(G::Position, usize) -> f32 + 'static,
+    ) -> Self {
+        self.weight_map = Some(Box::new(weight_map));
+        self
+    }
+
+    /// Set the [SelectionHeuristic] which decides what cell is collapsed next.
+    /// The default is [EntropyHeuristic::RemainingTiles].
+    pub fn with_heuristic(
+        mut self,
+        heuristic: impl SelectionHeuristic<C, G> + 'static,
+    ) -> Self {
+        self.heuristic = Some(Box::new(heuristic));
+        self
+    }
+
     pub fn with_constraint(
         mut self,
-        constraint: impl Constraint<T> + 'static,
+        constraint: impl Constraint<T, G> + 'static,
     ) -> Self {
         self.tile_constraints.add_constraint(constraint);
         self
     }
 
+    /// Enable backtracking, so a contradiction does not end the wfc immediately. Instead, the
+    /// decision which led to the contradiction gets undone and the wfc continues with the remaining tiles.
+    ///
+    /// * `max_depth` - How many of the latest decisions are remembered and can be undone. Every remembered decision holds a copy of the board.
+    /// * `max_backtracks` - How many times a decision can be undone before the wfc gives up.
+    pub fn with_backtracking(
+        mut self,
+        max_depth: usize,
+        max_backtracks: usize,
+    ) -> Self {
+        self.backtracking = Some(Backtracking {
+            max_depth,
+            max_backtracks,
+        });
+        self
+    }
+
+    /// Register an [Observer], which gets notified about the events during the collapse.
+    pub fn with_observer(
+        mut self,
+        observer: impl Observer<T, G::Position> + 'static,
+    ) -> Self {
+        self.observers.add_observer(observer);
+        self
+    }
+
+    /// Set how many times the wfc is restarted if it runs into a contradiction.
+    ///
+    /// Every retry starts with the board from before the first attempt. The seed for a retry is the
+    /// original seed plus the number of the attempt, so the result stays reproducible.
+    pub fn with_retries(
+        mut self,
+        retries: usize,
+    ) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Set if the constraints are compiled into an adjacency propagator if possible, or if the
+    /// constraints propagate themselves.
+    #[cfg(test)]
+    pub(crate) fn with_compiled_constraints(
+        mut self,
+        compiled: bool,
+    ) -> Self {
+        self.tile_constraints.skip_compilation = !compiled;
+        self
+    }
+
     /// Validate the input and create a [Wfc].
-    pub fn build(self) -> Result<Wfc<T, C>, WfcError> {
-        let board = Board::<C>::new(self.width, self.height, self.tiles.len(), &self.weights);
+    ///
+    /// Fails if the [Topology] is invalid (see [Topology::validate]), if the amount of weights differs from
+    /// the amount of tiles, or if the constraints leave no tile for a cell, even before the first tile was chosen.
+    pub fn build(self) -> WfcResult<Wfc<T, C, G>, G> {
+        self.topology.validate()?;
+
+        // tile indices are stored as u16, where u16::MAX is reserved as placeholder
+        let max_tiles = C::MAX_TILES.min(u16::MAX as usize);
+
+        if self.tiles.len() > max_tiles {
+            return Err(WfcError::TooManyTiles {
+                max: max_tiles,
+                was: self.tiles.len(),
+            });
+        }
+
+        if self.weights.len() != self.tiles.len() {
+            return Err(WfcError::WrongNumberOfWeights {
+                expected: self.tiles.len(),
+                was: self.weights.len(),
+            });
+        }
+
+        let mut tile_constraints = self.tile_constraints;
+        tile_constraints.compile(&self.tiles, &self.topology);
+
+        let mut random = self.random;
+        let mut board = Board::<C, G>::new(
+            self.topology,
+            self.tiles.len(),
+            self.weights,
+            self.weight_map.as_deref(),
+        );
+        let heuristic = self
+            .heuristic
+            .unwrap_or_else(|| Box::new(EntropyHeuristic::default()));
+        board.track_entropy(heuristic.entropy_heuristic());
+        board.track_supports(tile_constraints.propagator());
+
+        let mut observers = self.observers;
+        board.remove_unsupported_tiles(tile_constraints.propagator(), &mut observers)?;
+        let fresh_board = board.snapshot();
+        board.randomize_tie_breakers(&mut random);
 
         Ok(Wfc {
             board,
             tiles: self.tiles,
-            tile_constraints: self.tile_constraints,
-            random: self.random,
+            tile_constraints,
+            random,
+            heuristic,
+            backtracker: self.backtracking.map(Backtracker::new),
+            retries: self.retries,
+            fresh_board,
+            initial_board: None,
+            attempt: 0,
+            observers,
+            finished: false,
         })
     }
 }
 
+impl<T, C: Cell> WfcBuilder<Oriented<T>, C>
+where
+    T: Clone + Symmetric,
+{
+    /// Create a [WfcBuilder] for all rotations and reflections of the given tiles, according to their
+    /// [crate::symmetry::Symmetry]. The tiles are expanded with [Oriented::expand], so weights must be
+    /// provided for every variant in this order.
+    pub fn new_with_symmetry(
+        width: usize,
+        height: usize,
+        tiles: Vec<T>,
+    ) -> Self {
+        Self::new(width, height, Oriented::expand(tiles))
+    }
+}
+
 /// The struct which performs the wave function collapse.
-pub struct Wfc<T: Clone, C: Cell> {
-    board: Board<C>,
+pub struct Wfc<T: Clone, C: Cell, G: Topology = SquareGrid> {
+    board: Board<C, G>,
     tiles: Vec<T>,
-    tile_constraints: TileConstraints<T>,
+    tile_constraints: TileConstraints<T, G>,
     random: Random,
+    heuristic: Box<dyn SelectionHeuristic<C, G>>,
+    backtracker: Option<Backtracker<C>>,
+    retries: usize,
+    /// The board right after it was built, which is restored by [Wfc::reset]
+    fresh_board: BoardSnapshot<C>,
+    /// The board before the first attempt of a collapse, which is restored for every retry
+    initial_board: Option<BoardSnapshot<C>>,
+    /// The attempt of the last collapse
+    attempt: usize,
+    observers: Observers<T, G::Position>,
+    /// Tells if the observers were already notified that the collapse finished, so [Wfc::step]
+    /// only notifies them once
+    finished: bool,
 }
 
-impl<T, C: Cell> Wfc<T, C>
+impl<T, C: Cell, G: Topology> Wfc<T, C, G>
 where
     T: Clone,
 {
     /// Collapse the WFC until no more tiles are not collapsed.
-    pub fn collapse(mut self) -> Result<Vec<(Position, T)>, WfcError> {
-        while !self.board.collapsed() {
-            let (pos, cell) = self.board.get_min_entropy_position();
+    ///
+    /// The result is the [Topology::Grid] of the board, which is a [WfcGrid] for grids.
+    pub fn collapse(self) -> WfcResult<G::Grid<T>, G> {
+        self.collapse_with_attempt().map(|(_, tiles)| tiles)
+    }
 
-            let possible_indices = cell.get_possible_indices();
-            let weights = possible_indices
-                .iter()
-                .map(|i| self.board.weights[i as usize]);
-            let index = Self::choose_next_index(&mut self.random, possible_indices, weights);
-            self.board.collapse_position(pos, index);
-            self.board
-                .propagate(pos, &mut self.tile_constraints, &self.tiles)?;
+    /// Collapse the WFC like [Wfc::collapse], but also return the attempt which succeeded.
+    /// The first attempt is 0, every retry (see [WfcBuilder::with_retries]) increments it.
+    pub fn collapse_with_attempt(mut self) -> WfcResult<(usize, G::Grid<T>), G> {
+        let tiles = self.collapse_in_place()?;
+        Ok((self.attempt, tiles))
+    }
+
+    /// Collapse the WFC like [Wfc::collapse], but without consuming it.
+    ///
+    /// Together with [Wfc::reset], this allows to generate many results with the same
+    /// tiles and constraints while reusing all allocations of the wfc. Only the returned
+    /// [Topology::Grid] is allocated anew on every call.
+    pub fn collapse_in_place(&mut self) -> WfcResult<G::Grid<T>, G> {
+        self.attempt = 0;
+        self.finished = false;
+
+        if self.retries > 0 {
+            match &mut self.initial_board {
+                Some(initial_board) => self.board.snapshot_into(initial_board),
+                None => self.initial_board = Some(self.board.snapshot()),
+            }
+        }
+
+        while let Err(error) = self.run() {
+            let Some(initial_board) = self
+                .initial_board
+                .as_ref()
+                .filter(|_| self.attempt < self.retries)
+            else {
+                self.finish(Err(&error));
+                return Err(error);
+            };
+
+            self.attempt += 1;
+            self.board.restore(initial_board);
+            self.random.reseed(self.attempt);
+            self.board.randomize_tie_breakers(&mut self.random);
+
+            if let Some(backtracker) = &mut self.backtracker {
+                backtracker.reset();
+            }
         }
 
-        Ok(self
+        self.finish(Ok(()));
+
+        let tiles = self
             .board
             .get_collapsed_indices()
-            .map(|(pos, index)| (pos, self.tiles[index].clone()))
-            .collect())
+            .map(|index| self.tiles[index].clone())
+            .collect();
+
+        Ok(self.board.topology().grid(tiles))
+    }
+
+    /// Reset the wfc to its initial state, so it can be collapsed again with the given seed.
+    /// Tiles collapsed via [Wfc::collapse_tiles] are reset as well.
+    pub fn reset(
+        &mut self,
+        seed: impl Hash,
+    ) {
+        self.board.restore(&self.fresh_board);
+        self.random = Random::from_seed(seed);
+        self.board.randomize_tie_breakers(&mut self.random);
+        self.attempt = 0;
+        self.finished = false;
+
+        if let Some(backtracker) = &mut self.backtracker {
+            backtracker.reset();
+        }
+    }
+
+    /// Return the attempt which succeeded in the last collapse. The first attempt is 0,
+    /// every retry (see [WfcBuilder::with_retries]) increments it.
+    pub fn attempt(&self) -> usize {
+        self.attempt
+    }
+
+    /// Perform a single step of the wfc, which collapses the position with the lowest entropy
+    /// and propagates the change to the rest of the board.
+    ///
+    /// Returns a [Step] describing what happened, or None if the board is already fully collapsed.
+    /// The observers are notified that the collapse finished only once, even if this is called again afterward.
+    pub fn step(&mut self) -> WfcResult<Option<Step<T, G::Position>>, G> {
+        let result = match self.observe_and_propagate() {
+            Ok(result) => result,
+            Err(error) => {
+                self.finish(Err(&error));
+                return Err(error);
+            }
+        };
+
+        let Some((position, index, backtracked)) = result else {
+            self.finish(Ok(()));
+            return Ok(None);
+        };
+
+        Ok(Some(Step {
+            position,
+            tile: self.tiles[index as usize].clone(),
+            changed_positions: self.board.changed_positions().collect(),
+            backtracked,
+        }))
+    }
+
+    /// Return an [Iterator] which performs a [Step] of the wfc on every call to next.
+    /// This can be used to watch the wfc work.
+    pub fn steps(&mut self) -> Steps<'_, T, C, G> {
+        Steps::new(self)
+    }
+
+    /// Return all tiles which are still possible at the given position.
+    pub fn get_possible_tiles(
+        &self,
+        position: G::Position,
+    ) -> impl Iterator<Item = &T> {
+        self.board
+            .get_cell(position)
+            .get_possible_indices()
+            .into_iter()
+            .map(|i| &self.tiles[i as usize])
+    }
+
+    /// Notify the observers that the collapse finished with the given result, unless this already happened
+    /// since the last [Wfc::reset] or [Wfc::collapse_in_place].
+    fn finish(
+        &mut self,
+        result: Result<(), &WfcError<G::Position>>,
+    ) {
+        if !self.finished {
+            self.finished = true;
+            self.observers.finished(result);
+        }
+    }
+
+    /// Perform the collapse until the board is fully collapsed or a contradiction occurred.
+    fn run(&mut self) -> WfcResult<(), G> {
+        while self.observe_and_propagate()?.is_some() {}
+
+        Ok(())
+    }
+
+    /// Collapse the position with the lowest entropy and propagate the collapse.
+    ///
+    /// Returns the collapsed position, the chosen tile index and if backtracking was necessary,
+    /// or None if the board is already fully collapsed.
+    fn observe_and_propagate(&mut self) -> WfcResult<Option<(G::Position, u16, bool)>, G> {
+        self.board.clear_changed_positions();
+
+        if self.board.collapsed() {
+            return Ok(None);
+        }
+
+        let pos = self.heuristic.select(BoardView::new(&self.board));
+        let cell_index = self.board.cell_index(pos);
+        let cell = self.board.get_cell(pos);
+
+        assert!(
+            !cell.is_collapsed(),
+            "The selection heuristic should return a position which is not collapsed yet"
+        );
+
+        let possible_indices = cell.get_possible_indices();
+        let weights = possible_indices
+            .iter()
+            .map(|i| self.board.get_weight(pos, i));
+        let Some(index) = Self::choose_next_index(&mut self.random, possible_indices, weights)
+        else {
+            return Err(WfcError::InvalidWeights(pos));
+        };
+
+        if let Some(backtracker) = &mut self.backtracker {
+            backtracker.record(&self.board, cell_index, index);
+        }
+
+        self.board.collapse_cell(cell_index, index);
+        self.observers
+            .cell_collapsed(pos, &self.tiles[index as usize]);
+
+        let Err(error) = self.board.propagate(
+            cell_index,
+            &mut self.tile_constraints,
+            &self.tiles,
+            &mut self.observers,
+        ) else {
+            return Ok(Some((pos, index, false)));
+        };
+
+        match &mut self.backtracker {
+            Some(backtracker) => backtracker.backtrack(
+                &mut self.board,
+                &mut self.tile_constraints,
+                &self.tiles,
+                &mut self.observers,
+                error,
+            )?,
+            None => return Err(error),
+        }
+
+        Ok(Some((pos, index, true)))
     }
 
     fn choose_next_index(
         random: &mut Random,
         possible_indices: PossibleIndices,
         tile_weights: impl IntoIterator<Item = f32>,
-    ) -> u8 {
+    ) -> Option<u16> {
         random.choose_weighted(tile_weights, possible_indices)
     }
 }
 
-impl<T, C: Cell> Wfc<T, C>
+impl<T, C: Cell, G: Topology> Wfc<T, C, G>
 where
     T: Clone + PartialEq,
 {
@@ -150,45 +618,64 @@ where
     /// due to the constraints. It might render the WFC impossible to solve.
     pub fn collapse_tiles(
         &mut self,
-        tiles: impl IntoIterator<Item = (Position, T)>,
-    ) -> Result<(), WfcError> {
-        let tiles = tiles.into_iter().collect::<Vec<_>>();
+        tiles: impl IntoIterator<Item = (G::Position, T)>,
+    ) -> WfcResult<(), G> {
+        let tiles = tiles
+            .into_iter()
+            .map(|(pos, tile)| (self.board.cell_index(pos), pos, tile))
+            .collect::<Vec<_>>();
         let get_tile_index = |tile: &T| {
             self.tiles
                 .iter()
                 .enumerate()
                 .find(|(_, t)| *t == tile)
                 .unwrap()
-                .0 as u8
+                .0 as u16
         };
 
-        for (pos, t) in &tiles {
-            self.board.collapse_position(*pos, get_tile_index(t));
+        for (cell, pos, t) in &tiles {
+            self.board.collapse_cell(*cell, get_tile_index(t));
+            self.observers.cell_collapsed(*pos, t);
         }
 
-        for (pos, _) in tiles {
-            self.board
-                .propagate(pos, &mut self.tile_constraints, &self.tiles)?
+        for (cell, _, _) in tiles {
+            self.board.propagate(
+                cell,
+                &mut self.tile_constraints,
+                &self.tiles,
+                &mut self.observers,
+            )?
         }
 
         Ok(())
     }
 }
 
+/// The [Result] of an operation of a [Wfc] with the given [Topology].
+pub type WfcResult<T, G = SquareGrid> = Result<T, WfcError<<G as Topology>::Position>>;
+
 /// An error for the wave function collapse, which occurs due to configuration errors
-/// or runtime errors.
+/// or runtime errors. The position of a cell is given in the coordinates of the [Topology].
 #[derive(Debug)]
-pub enum WfcError {
+pub enum WfcError<P = Position> {
     /// More tile types are provided than supported by the WFC
     TooManyTiles { max: usize, was: usize },
+    /// The amount of weights given to [WfcBuilder::with_weights] differs from the amount of tiles
+    WrongNumberOfWeights { expected: usize, was: usize },
     /// A cell has zero entropy after a propagation, which means no tile
     /// can be picked for it
-    CellHasZeroEntropy(Position),
+    CellHasZeroEntropy(P),
+    /// The [Topology] of the board does not describe a valid board, for the given reason
+    InvalidTopology(String),
+    /// No tile can be chosen for a cell, because the weight of a possible tile is negative or not finite, or
+    /// the weights of all possible tiles are zero. The weights result from [WfcBuilder::with_weights],
+    /// [WfcBuilder::with_weight_map] and the weight modifiers of the constraints.
+    InvalidWeights(P),
 }
 
-impl Error for WfcError {}
+impl<P: Debug> Error for WfcError<P> {}
 
-impl Display for WfcError {
+impl<P: Debug> Display for WfcError<P> {
     fn fmt(
         &self,
         f: &mut Formatter<'_>,
@@ -198,10 +685,956 @@ impl Display for WfcError {
                 f,
                 "{was} tiles where provided, but only {max} are supported!"
             ),
+            WfcError::WrongNumberOfWeights { expected, was } => write!(
+                f,
+                "{was} weights where provided, but there are {expected} tiles!"
+            ),
             WfcError::CellHasZeroEntropy(pos) => write!(
                 f,
                 "The position {pos:?} has zero entropy and cannot be collapsed!"
             ),
+            WfcError::InvalidTopology(reason) => write!(f, "The topology is invalid: {reason}"),
+            WfcError::InvalidWeights(pos) => write!(
+                f,
+                "No tile can be chosen at the position {pos:?}, as the weights of its tiles are invalid!"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cell::{BitCell, DynCell},
+        constraints::{DirectionalNeighbours, NeighbourBias, PossibleNeighbours},
+        direction::{Direction, HexDirection, Undirected, VoxelDirection},
+        heuristic::Scanline,
+        topology::{Graph, HexGrid, Topology, VoxelPosition},
+    };
+    use pad::p;
+    use std::{cell::RefCell, rc::Rc};
+
+    /// A [Constraint] for the tiles 0 to 3. The right neighbour of a tile is found by swapping 0 and 1,
+    /// the neighbour below by swapping 1 and 2. Both ways around a square of cells end with different
+    /// tiles, so only the tile 3, which is only next to itself, fits on boards with several rows and columns.
+    struct SwappingRules;
+
+    impl Constraint<u8> for SwappingRules {
+        fn valid(
+            &self,
+            (tile, _): (u16, Position),
+            neighbours: &[(PossibleIndices, Direction)],
+            _tiles: &[u8],
+        ) -> Option<f32> {
+            // both swaps are their own inverse, so the side of the neighbour does not matter
+            let swap = |tile, (a, b)| match tile {
+                t if t == a => b,
+                t if t == b => a,
+                t => t,
+            };
+
+            neighbours
+                .iter()
+                .all(|(possible, dir)| {
+                    let allowed = match dir {
+                        Direction::Left | Direction::Right => swap(tile, (0, 1)),
+                        Direction::Up | Direction::Down => swap(tile, (1, 2)),
+                    };
+                    possible.iter().any(|t| t == allowed)
+                })
+                .then_some(1.0)
+        }
+    }
+
+    /// The [SwappingRules] as [DirectionalNeighbours], which can be compiled into an adjacency propagator.
+    fn swapping_neighbours() -> DirectionalNeighbours {
+        let rules = (0..4).flat_map(|tile| {
+            let (right, down) = match tile {
+                0 => (1, 0),
+                1 => (0, 2),
+                2 => (2, 1),
+                t => (t, t),
+            };
+
+            [
+                (tile, Direction::Right, right),
+                (tile, Direction::Down, down),
+            ]
+        });
+
+        DirectionalNeighbours::new(rules, &[0, 1, 2, 3])
+    }
+
+    /// Rules for the tiles 0 to 3, where 0 is the sky, 1 is grass below it, and 2 and 3 are the
+    /// ground below the grass. The sky can only be in the top row, as nothing can be above it.
+    fn layer_rules() -> DirectionalNeighbours {
+        let horizontal = (0..4).flat_map(|t0| (0..4).map(move |t1| (t0, Direction::Right, t1)));
+        let vertical = [(0, 1), (1, 2), (1, 3), (2, 2), (2, 3), (3, 2), (3, 3)]
+            .map(|(t0, t1)| (t0, Direction::Down, t1));
+
+        DirectionalNeighbours::new(horizontal.chain(vertical), &[0, 1, 2, 3])
+    }
+
+    /// Assert that every tile of the grid is allowed next to all of its neighbours.
+    /// The tiles must be the same as their indices.
+    fn assert_follows_rules(
+        grid: &WfcGrid<u8>,
+        rules: &DirectionalNeighbours,
+    ) {
+        for (pos, tile) in grid.iter() {
+            for dir in Direction::ALL {
+                let (dx, dy) = dir.offset();
+
+                if let Some(neighbour) = grid.get(p!(pos.x + dx, pos.y + dy)) {
+                    assert!(
+                        rules.allowed(*tile as u16, dir, *neighbour as u16),
+                        "{neighbour} should not be {dir:?} of {tile} at {pos:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Create a builder for the tiles 0 to 3 following the [SwappingRules].
+    /// The tile 3 is rarely chosen, so most decisions on larger boards contradict.
+    fn builder(
+        width: usize,
+        height: usize,
+        seed: u64,
+    ) -> WfcBuilder<u8, BitCell> {
+        WfcBuilder::new(width, height, vec![0, 1, 2, 3])
+            .with_constraint(SwappingRules)
+            .with_weights([100.0, 100.0, 100.0, 1.0])
+            .with_seed(seed)
+    }
+
+    /// Create a builder like [builder], but following the [swapping_neighbours].
+    fn neighbours_builder(
+        width: usize,
+        height: usize,
+        seed: u64,
+    ) -> WfcBuilder<u8, BitCell> {
+        WfcBuilder::new(width, height, vec![0, 1, 2, 3])
+            .with_constraint(swapping_neighbours())
+            .with_weights([100.0, 100.0, 100.0, 1.0])
+            .with_seed(seed)
+    }
+
+    /// Make the first decision of the builder happen at `p!(0, 0)`, where the tile 3 is never chosen. As only
+    /// the tile 3 fits on boards with several rows and columns, this decision always contradicts.
+    fn contradicting_first_decision(builder: WfcBuilder<u8, BitCell>) -> WfcBuilder<u8, BitCell> {
+        builder
+            .with_heuristic(Scanline)
+            .with_weight_map(|pos, index| match pos == p!(0, 0) && index == 3 {
+                true => 0.0,
+                false => 1.0,
+            })
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Collapsed(Position, u8),
+        Removed(Position, Vec<u16>),
+        Contradiction(Position),
+        Finished(bool),
+    }
+
+    /// An [Observer] which records all events in order.
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<Event>,
+    }
+
+    impl Observer<u8> for Recorder {
+        fn cell_collapsed(
+            &mut self,
+            position: Position,
+            tile: &u8,
+        ) {
+            self.events.push(Event::Collapsed(position, *tile));
+        }
+
+        fn possibilities_removed(
+            &mut self,
+            position: Position,
+            removed_indices: &[u16],
+        ) {
+            self.events
+                .push(Event::Removed(position, removed_indices.to_vec()));
+        }
+
+        fn contradiction_found(
+            &mut self,
+            position: Position,
+        ) {
+            self.events.push(Event::Contradiction(position));
+        }
+
+        fn finished(
+            &mut self,
+            result: Result<(), &WfcError>,
+        ) {
+            self.events.push(Event::Finished(result.is_ok()));
+        }
+    }
+
+    #[test]
+    fn propagation_finds_contradictions_around_squares() {
+        for seed in 0..10 {
+            for compiled in [true, false] {
+                let result = contradicting_first_decision(neighbours_builder(2, 2, seed))
+                    .with_compiled_constraints(compiled)
+                    .build()
+                    .unwrap()
+                    .collapse();
+
+                assert!(matches!(result, Err(WfcError::CellHasZeroEntropy(_))));
+            }
+        }
+    }
+
+    #[test]
+    fn adjacency_and_constraint_propagation_follow_the_rules() {
+        for seed in 0..10 {
+            for compiled in [true, false] {
+                let builder = WfcBuilder::<u8, BitCell>::new(6, 4, vec![0, 1, 2, 3])
+                    .with_constraint(layer_rules())
+                    .with_weights([10.0, 1.0, 1.0, 1.0])
+                    .with_heuristic(Scanline)
+                    .with_seed(seed)
+                    .with_compiled_constraints(compiled);
+
+                let grid = builder.build().unwrap().collapse().unwrap();
+                assert_follows_rules(&grid, &layer_rules());
+            }
+        }
+    }
+
+    #[test]
+    fn masked_boards_ignore_excluded_cells() {
+        let rules = swapping_neighbours();
+
+        for seed in 0..10 {
+            for compiled in [true, false] {
+                // the ring around the hole has no square of cells, so the swapping rules fit on it
+                let builder = neighbours_builder(3, 3, seed)
+                    .with_mask(|pos| pos != p!(1, 1))
+                    .with_heuristic(Scanline)
+                    .with_compiled_constraints(compiled);
+
+                let grid = builder.build().unwrap().collapse().unwrap();
+                assert_eq!(grid.get(p!(1, 1)), Some(&None));
+
+                let ring = grid
+                    .iter()
+                    .filter(|(pos, _)| *pos != p!(1, 1))
+                    .map(|(pos, tile)| (pos, tile.unwrap()));
+
+                for (pos, tile) in ring {
+                    for dir in [Direction::Right, Direction::Down] {
+                        let (dx, dy) = dir.offset();
+
+                        if let Some(Some(neighbour)) = grid.get(p!(pos.x + dx, pos.y + dy)) {
+                            assert!(rules.allowed(tile as u16, dir, *neighbour as u16));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn backtracking_undoes_contradicting_decisions() {
+        for seed in 0..10 {
+            assert!(builder(3, 3, seed).build().unwrap().collapse().is_err());
+            assert!(
+                builder(3, 3, seed)
+                    .with_backtracking(0, 3)
+                    .build()
+                    .unwrap()
+                    .collapse()
+                    .is_err()
+            );
+            assert!(
+                builder(3, 3, seed)
+                    .with_backtracking(1, 0)
+                    .build()
+                    .unwrap()
+                    .collapse()
+                    .is_err()
+            );
+
+            let result = builder(3, 3, seed)
+                .with_backtracking(1, 3)
+                .build()
+                .unwrap()
+                .collapse()
+                .unwrap();
+            assert!(result.iter().all(|(_, tile)| *tile == 3));
+        }
+    }
+
+    #[test]
+    fn seeds_reproduce_results_and_attempts() {
+        // a single row never contradicts, because only the horizontal swap applies
+        let collapse = |seed| builder(6, 1, seed).build().unwrap().collapse().unwrap();
+        assert_eq!(collapse(1), collapse(1));
+        assert!((2..10).any(|seed| collapse(seed) != collapse(1)));
+
+        for seed in 0..10 {
+            // only attempts which start with the rare tile 3 succeed
+            let (attempt, grid) = builder(3, 3, seed)
+                .with_retries(5000)
+                .build()
+                .unwrap()
+                .collapse_with_attempt()
+                .unwrap();
+            assert!(attempt > 0);
+
+            let (same_attempt, same_grid) = builder(3, 3, seed)
+                .with_retries(attempt)
+                .build()
+                .unwrap()
+                .collapse_with_attempt()
+                .unwrap();
+            assert_eq!((attempt, grid), (same_attempt, same_grid));
+            assert!(
+                builder(3, 3, seed)
+                    .with_retries(attempt - 1)
+                    .build()
+                    .unwrap()
+                    .collapse()
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn ties_are_broken_randomly() {
+        // every cell has the same entropy before the first step
+        let first_position = |seed| {
+            WfcBuilder::<u8, BitCell>::new(3, 3, vec![0, 1])
+                .with_seed(seed)
+                .build()
+                .unwrap()
+                .step()
+                .unwrap()
+                .unwrap()
+                .position
+        };
+        let positions = (0..10).map(first_position).collect::<Vec<_>>();
+
+        assert_eq!(positions, (0..10).map(first_position).collect::<Vec<_>>());
+        assert!(positions.iter().any(|pos| *pos != positions[0]));
+        assert!(positions.iter().any(|pos| *pos != p!(0, 0)));
+    }
+
+    #[test]
+    fn retries_break_ties_differently() {
+        let recorder = Rc::new(RefCell::new(Recorder::default()));
+
+        builder(3, 3, 0)
+            .with_retries(5000)
+            .with_observer(recorder.clone())
+            .build()
+            .unwrap()
+            .collapse()
+            .unwrap();
+
+        // every attempt but the first one starts after a contradiction
+        let events = &recorder.borrow().events;
+        let attempt_starts = events
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i == 0 || matches!(events[i - 1], Event::Contradiction(_)))
+            .filter_map(|(_, event)| match event {
+                Event::Collapsed(pos, _) => Some(*pos),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert!(attempt_starts.len() > 1);
+        assert!(attempt_starts.iter().any(|pos| *pos != attempt_starts[0]));
+    }
+
+    #[test]
+    fn entropy_queue_selects_the_same_cells_as_a_scan() {
+        // the swapping rules contradict often, so the queue also gets rebuilt after backtracking
+        let combinations = [layer_rules(), swapping_neighbours()]
+            .into_iter()
+            .flat_map(|rules| [true, false].map(|compiled| (rules.clone(), compiled)));
+
+        for (rules, compiled) in combinations {
+            for heuristic in [EntropyHeuristic::RemainingTiles, EntropyHeuristic::Shannon] {
+                for seed in 0..5 {
+                    let builder = || {
+                        WfcBuilder::<u8, BitCell>::new(16, 12, vec![0, 1, 2, 3])
+                            .with_constraint(rules.clone())
+                            .with_weights([10.0, 1.0, 3.0, 2.0])
+                            .with_backtracking(10, 1000)
+                            .with_compiled_constraints(compiled)
+                            .with_seed(seed)
+                    };
+
+                    // a closure has no entropy heuristic, so the board scans all cells instead of using its queue
+                    let mut queued = builder().with_heuristic(heuristic).build().unwrap();
+                    let mut scanned = builder()
+                        .with_heuristic(move |board: BoardView<'_, BitCell>| {
+                            board.min_entropy_position(heuristic)
+                        })
+                        .build()
+                        .unwrap();
+
+                    loop {
+                        let step = |wfc: &mut Wfc<u8, BitCell>| {
+                            wfc.step()
+                                .map(|step| {
+                                    step.map(|step| (step.position, step.tile, step.backtracked))
+                                })
+                                .map_err(|error| error.to_string())
+                        };
+                        let expected = step(&mut scanned);
+
+                        assert_eq!(step(&mut queued), expected);
+
+                        if !matches!(expected, Ok(Some(_))) {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn steps_report_collapsed_and_shrunk_positions() {
+        let positions = (0..4)
+            .flat_map(|y| (0..6).map(move |x| p!(x, y)))
+            .collect::<Vec<_>>();
+        let possible_counts = |wfc: &Wfc<u8, BitCell>| {
+            positions
+                .iter()
+                .map(|pos| wfc.get_possible_tiles(*pos).count())
+                .collect::<Vec<_>>()
+        };
+
+        for seed in 0..10 {
+            for compiled in [true, false] {
+                // the layer rules never contradict row by row, so no step backtracks
+                let builder = WfcBuilder::<u8, BitCell>::new(6, 4, vec![0, 1, 2, 3])
+                    .with_constraint(layer_rules())
+                    .with_weights([10.0, 1.0, 1.0, 1.0])
+                    .with_heuristic(Scanline)
+                    .with_seed(seed)
+                    .with_compiled_constraints(compiled);
+
+                let mut wfc = builder.build().unwrap();
+                let mut before = possible_counts(&wfc);
+
+                while let Some(step) = wfc.step().unwrap() {
+                    let after = possible_counts(&wfc);
+
+                    assert!(!step.backtracked);
+                    assert_eq!(
+                        wfc.get_possible_tiles(step.position).collect::<Vec<_>>(),
+                        vec![&step.tile]
+                    );
+
+                    // the positions are ordered by their cell index, which is row-major like the positions
+                    let shrunk = positions
+                        .iter()
+                        .zip(before.iter().zip(after.iter()))
+                        .filter(|(pos, (b, a))| a < b && **pos != step.position)
+                        .map(|(pos, _)| *pos)
+                        .collect::<Vec<_>>();
+                    assert_eq!(step.changed_positions, shrunk);
+
+                    before = after;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reset_collapses_like_a_fresh_wfc() {
+        let mut wfc = builder(6, 1, 0).build().unwrap();
+        wfc.collapse_in_place().unwrap();
+
+        for seed in 0..10 {
+            // tiles collapsed by hand must be forgotten as well
+            wfc.reset(seed);
+            wfc.collapse_tiles([(p!(0, 0), 3)]).unwrap();
+            wfc.reset(seed);
+
+            let fresh = builder(6, 1, seed).build().unwrap();
+
+            assert_eq!(wfc.collapse_in_place().unwrap(), fresh.collapse().unwrap());
+        }
+    }
+
+    #[test]
+    fn observers_are_notified_about_backtracking() {
+        for seed in 0..10 {
+            let recorder = Rc::new(RefCell::new(Recorder::default()));
+
+            let grid = contradicting_first_decision(builder(2, 2, seed))
+                .with_backtracking(1, 3)
+                .with_observer(recorder.clone())
+                .build()
+                .unwrap()
+                .collapse()
+                .unwrap();
+
+            assert!(grid.iter().all(|(_, tile)| *tile == 3));
+
+            let events = &recorder.borrow().events;
+            let Some(Event::Collapsed(pos, tile)) = events.first() else {
+                panic!("The first event should be the first decision");
+            };
+
+            // the first decision contradicts, so its tile gets removed from its position when it is undone
+            assert_eq!(*pos, p!(0, 0));
+            assert!(events.contains(&Event::Removed(*pos, vec![*tile as u16])));
+
+            assert!(
+                events
+                    .iter()
+                    .any(|event| matches!(event, Event::Contradiction(_)))
+            );
+            assert_eq!(events.last(), Some(&Event::Finished(true)));
+        }
+    }
+
+    #[test]
+    fn observers_are_notified_about_failed_steps() {
+        let recorder = Rc::new(RefCell::new(Recorder::default()));
+        let mut wfc = builder(3, 3, 0)
+            .with_observer(recorder.clone())
+            .build()
+            .unwrap();
+
+        assert!(wfc.steps().last().unwrap().is_err());
+
+        let events = &recorder.borrow().events;
+        assert!(matches!(events[events.len() - 2], Event::Contradiction(_)));
+        assert_eq!(events.last(), Some(&Event::Finished(false)));
+    }
+
+    #[test]
+    fn observers_are_notified_once_about_finished_steps() {
+        let recorder = Rc::new(RefCell::new(Recorder::default()));
+        let mut wfc = builder(6, 1, 0)
+            .with_observer(recorder.clone())
+            .build()
+            .unwrap();
+        let num_finished = |recorder: &Rc<RefCell<Recorder>>| {
+            recorder
+                .borrow()
+                .events
+                .iter()
+                .filter(|event| matches!(event, Event::Finished(_)))
+                .count()
+        };
+
+        while wfc.step().unwrap().is_some() {}
+
+        assert!(wfc.step().unwrap().is_none());
+        assert!(wfc.step().unwrap().is_none());
+        assert_eq!(num_finished(&recorder), 1);
+
+        wfc.reset(1);
+        wfc.collapse_in_place().unwrap();
+        assert!(wfc.step().unwrap().is_none());
+        assert_eq!(num_finished(&recorder), 2);
+    }
+
+    /// A [Constraint] which never allows two equal tiles next to each other, but makes the tile 1
+    /// a lot more likely everywhere else.
+    struct FavouredOnesApart;
+
+    impl Constraint<u8> for FavouredOnesApart {
+        fn valid(
+            &self,
+            (tile, _): (u16, Position),
+            neighbours: &[(PossibleIndices, Direction)],
+            _tiles: &[u8],
+        ) -> Option<f32> {
+            let next_to_same = neighbours
+                .iter()
+                .any(|(possible, _)| possible.entropy() == 1 && possible.get(0) == tile);
+
+            match (next_to_same, tile) {
+                (true, _) => None,
+                (false, 1) => Some(1000.0),
+                (false, _) => Some(1.0),
+            }
+        }
+    }
+
+    #[test]
+    fn weight_modifiers_change_the_tile_frequencies() {
+        let num_zeros = |bias: Option<NeighbourBias>| {
+            (0..20)
+                .map(|seed| {
+                    let mut builder = WfcBuilder::<u8, BitCell>::new(12, 1, vec![0, 1])
+                        .with_heuristic(Scanline)
+                        .with_seed(seed);
+
+                    if let Some(bias) = &bias {
+                        builder = builder.with_constraint(bias.clone());
+                    }
+
+                    let grid = builder.build().unwrap().collapse().unwrap();
+                    grid.iter().filter(|(_, tile)| **tile == 0).count()
+                })
+                .sum::<usize>()
+        };
+
+        // a zero next to a zero gets a lot more likely
+        let bias = NeighbourBias::new([(0, 0, 50.0)], &[0, 1]);
+        assert!(num_zeros(Some(bias)) > num_zeros(None) * 3 / 2);
+    }
+
+    #[test]
+    fn weight_modifiers_do_not_allow_rejected_tiles() {
+        for seed in 0..10 {
+            let grid = WfcBuilder::<u8, BitCell>::new(12, 1, vec![0, 1])
+                .with_constraint(FavouredOnesApart)
+                .with_heuristic(Scanline)
+                .with_seed(seed)
+                .build()
+                .unwrap()
+                .collapse()
+                .unwrap();
+
+            for x in 1..12 {
+                assert_ne!(grid[p!(x - 1, 0)], grid[p!(x, 0)]);
+            }
+        }
+    }
+
+    #[test]
+    fn weight_maps_keep_zeroed_tiles_out_of_their_region() {
+        for seed in 0..10 {
+            for heuristic in [EntropyHeuristic::RemainingTiles, EntropyHeuristic::Shannon] {
+                // the tile 0 is impossible in the left half, and as likely as the other tiles in the right half
+                let grid = WfcBuilder::<u8, BitCell>::new(8, 4, vec![0, 1, 2])
+                    .with_weight_map(|pos, index| match pos.x < 4 && index == 0 {
+                        true => 0.0,
+                        false => 1.0,
+                    })
+                    .with_heuristic(heuristic)
+                    .with_seed(seed)
+                    .build()
+                    .unwrap()
+                    .collapse()
+                    .unwrap();
+
+                let (left, right): (Vec<_>, Vec<_>) = grid.iter().partition(|(pos, _)| pos.x < 4);
+                assert!(left.iter().all(|(_, tile)| **tile != 0));
+                assert!(right.iter().any(|(_, tile)| **tile == 0));
+            }
+        }
+    }
+
+    #[test]
+    fn too_many_tiles_are_reported() {
+        let result = WfcBuilder::<u8, BitCell>::new(3, 3, (0..129).collect()).build();
+
+        assert!(matches!(
+            result,
+            Err(WfcError::TooManyTiles { max: 128, was: 129 })
+        ));
+    }
+
+    #[test]
+    fn more_than_255_tiles_can_be_collapsed() {
+        // every tile is followed by the next one to the right and below, so the first row holds all tiles
+        let tiles = (0..300).collect::<Vec<u16>>();
+        let next = |tile: u16| (tile + 1) % 300;
+        let rules = DirectionalNeighbours::new(
+            tiles.iter().flat_map(|t| {
+                [
+                    (*t, Direction::Right, next(*t)),
+                    (*t, Direction::Down, next(*t)),
+                ]
+            }),
+            &tiles,
+        );
+
+        for compiled in [true, false] {
+            let grid = WfcBuilder::<u16, DynCell>::new(300, 2, tiles.clone())
+                .with_constraint(rules.clone())
+                .with_compiled_constraints(compiled)
+                .with_seed(0)
+                .build()
+                .unwrap()
+                .collapse()
+                .unwrap();
+
+            for (pos, tile) in grid.iter() {
+                for dir in [Direction::Right, Direction::Down] {
+                    let (dx, dy) = dir.offset();
+
+                    if let Some(neighbour) = grid.get(p!(pos.x + dx, pos.y + dy)) {
+                        assert_eq!(*neighbour, next(*tile));
+                    }
+                }
+            }
+
+            let mut first_row = (0..300)
+                .map(|x| *grid.get(p!(x, 0)).unwrap())
+                .collect::<Vec<_>>();
+            first_row.sort();
+            assert_eq!(first_row, tiles);
+        }
+    }
+
+    #[test]
+    fn weights_must_match_the_tiles() {
+        for weights in [vec![1.0, 1.0], vec![1.0; 4]] {
+            let result = WfcBuilder::<u8, BitCell>::new(3, 3, vec![0, 1, 2])
+                .with_weights(weights)
+                .build();
+
+            assert!(matches!(
+                result,
+                Err(WfcError::WrongNumberOfWeights { expected: 3, .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn invalid_weights_are_reported() {
+        for weights in [[0.0, 0.0], [1.0, -1.0], [1.0, f32::NAN]] {
+            let result = WfcBuilder::<u8, BitCell>::new(3, 1, vec![0, 1])
+                .with_weights(weights)
+                .build()
+                .unwrap()
+                .collapse();
+
+            assert!(matches!(result, Err(WfcError::InvalidWeights(_))));
+        }
+    }
+
+    #[test]
+    fn graphs_can_have_several_neighbours_in_a_direction() {
+        // a wheel, where the hub is connected to every cell of a rim with six cells
+        let mut wheel = Graph::new(7);
+
+        for cell in 1..7 {
+            wheel.add_edge(0, Undirected, cell);
+            wheel.add_edge(cell, Undirected, cell % 6 + 1);
+        }
+
+        // colour the wheel with three colours, so no neighbours have the same one
+        let colours = vec![0, 1, 2];
+        let different = colours.iter().flat_map(|c0| {
+            colours
+                .iter()
+                .filter(move |c1| c0 != *c1)
+                .map(|c1| (*c0, *c1))
+        });
+
+        // the right neighbours of the first cell are the opposites of it
+        let fork =
+            Graph::new(3)
+                .with_edge(0, Direction::Right, 1)
+                .with_edge(0, Direction::Right, 2);
+        let tiles = vec![0, 1];
+        let opposites = DirectionalNeighbours::new(
+            [(0, Direction::Right, 1), (1, Direction::Right, 0)],
+            &tiles,
+        );
+
+        for seed in 0..10 {
+            let colouring =
+                WfcBuilder::<u8, BitCell, _>::from_topology(wheel.clone(), colours.clone())
+                    .with_constraint(PossibleNeighbours::new(different.clone(), &colours))
+                    .with_backtracking(7, 100)
+                    .with_seed(seed)
+                    .build()
+                    .unwrap()
+                    .collapse()
+                    .unwrap();
+
+            for cell in 0..7 {
+                for (_, neighbour) in wheel.neighbours(cell) {
+                    assert_ne!(colouring[cell], colouring[neighbour]);
+                }
+            }
+
+            let tiles = WfcBuilder::<u8, BitCell, _>::from_topology(fork.clone(), tiles.clone())
+                .with_constraint(opposites.clone())
+                .with_seed(seed)
+                .build()
+                .unwrap()
+                .collapse()
+                .unwrap();
+
+            assert_ne!(tiles[0], tiles[1]);
+            assert_eq!(tiles[1], tiles[2]);
+        }
+    }
+
+    #[test]
+    fn voxel_grids_follow_vertical_rules() {
+        // 0 is the ground, 1 is grass on top of it and 2 is the air above the grass. Within a layer,
+        // every tile can be next to every other tile
+        let tiles = vec![0, 1, 2];
+        let horizontal = [
+            VoxelDirection::Up,
+            VoxelDirection::Down,
+            VoxelDirection::Left,
+            VoxelDirection::Right,
+        ]
+        .into_iter()
+        .flat_map(|dir| (0..3).flat_map(move |t0| (0..3).map(move |t1| (t0, dir, t1))));
+        let vertical =
+            [(0, 0), (0, 1), (1, 2), (2, 2)].map(|(t0, t1)| (t0, VoxelDirection::Above, t1));
+        let rules = DirectionalNeighbours::new(horizontal.chain(vertical), &tiles);
+
+        for seed in 0..10 {
+            for compiled in [true, false] {
+                let volume = WfcBuilder::<u8, BitCell, VoxelGrid>::new_3d(4, 3, 5, tiles.clone())
+                    .with_constraint(rules.clone())
+                    .with_compiled_constraints(compiled)
+                    .with_seed(seed)
+                    .build()
+                    .unwrap()
+                    .collapse()
+                    .unwrap();
+
+                for (pos, tile) in volume.iter() {
+                    for dir in [VoxelDirection::Above, VoxelDirection::Below] {
+                        let (dx, dy, dz) = dir.offset();
+                        let neighbour_pos = VoxelPosition::new(pos.x + dx, pos.y + dy, pos.z + dz);
+
+                        if let Some(neighbour) = volume.get(neighbour_pos) {
+                            assert!(
+                                rules.allowed(*tile as u16, dir, *neighbour as u16),
+                                "{neighbour} should not be {dir:?} of {tile} at {pos:?}"
+                            );
+                        }
+                    }
+                }
+
+                // from the bottom to the top, every column is ground, at most one grass and then air
+                for (x, y) in (0..4).flat_map(|x| (0..3).map(move |y| (x, y))) {
+                    let column = (0..5)
+                        .map(|z| volume[VoxelPosition::new(x, y, z)])
+                        .collect::<Vec<_>>();
+
+                    assert!(column.is_sorted(), "{column:?} at {x}, {y}");
+                    assert!(column.iter().filter(|tile| **tile == 1).count() <= 1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hex_grids_follow_directional_rules() {
+        // the three tiles colour the hexagons, so that the tiles around a hexagon alternate. The lower
+        // neighbours of a hexagon are each other's left and right neighbour, so the rules fit together
+        let tiles = vec![0, 1, 2];
+        let next = |tile: u8, steps: u8| (tile + steps) % 3;
+        let rules = DirectionalNeighbours::new(
+            tiles.iter().flat_map(|t| {
+                [
+                    (*t, HexDirection::Right, next(*t, 1)),
+                    (*t, HexDirection::DownLeft, next(*t, 1)),
+                    (*t, HexDirection::DownRight, next(*t, 2)),
+                ]
+            }),
+            &tiles,
+        );
+
+        for seed in 0..10 {
+            for compiled in [true, false] {
+                let grid = WfcBuilder::<u8, BitCell, HexGrid>::new_hex(7, 6, tiles.clone())
+                    .with_constraint(rules.clone())
+                    .with_compiled_constraints(compiled)
+                    .with_seed(seed)
+                    .build()
+                    .unwrap()
+                    .collapse()
+                    .unwrap();
+
+                for (pos, tile) in grid.iter() {
+                    for dir in HexDirection::ALL {
+                        let (dx, dy) = dir.offset(pos.y % 2 == 1);
+
+                        if let Some(neighbour) = grid.get(p!(pos.x + dx, pos.y + dy)) {
+                            assert!(
+                                rules.allowed(*tile as u16, dir, *neighbour as u16),
+                                "{neighbour} should not be {dir:?} of {tile} at {pos:?}"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn wrapped_boards_follow_the_rules_across_their_edges() {
+        // the tiles form vertical stripes which repeat every three columns
+        let tiles = vec![0, 1, 2];
+        let next = |tile: u8| (tile + 1) % 3;
+        let rules = DirectionalNeighbours::new(
+            tiles
+                .iter()
+                .flat_map(|t| [(*t, Direction::Right, next(*t)), (*t, Direction::Down, *t)]),
+            &tiles,
+        );
+        let builder = |width, seed| {
+            WfcBuilder::<u8, BitCell>::new(width, 4, tiles.clone())
+                .with_constraint(rules.clone())
+                .with_wrapping(true, true)
+                .with_seed(seed)
+        };
+
+        for seed in 0..10 {
+            for compiled in [true, false] {
+                let grid = builder(6, seed)
+                    .with_compiled_constraints(compiled)
+                    .build()
+                    .unwrap()
+                    .collapse()
+                    .unwrap();
+
+                // the neighbours of the last column and row are in the first column and row
+                for (pos, tile) in grid.iter() {
+                    let right = grid[p!((pos.x + 1) % 6, pos.y)];
+                    let below = grid[p!(pos.x, (pos.y + 1) % 4)];
+
+                    assert_eq!(right, next(*tile), "at {pos:?}");
+                    assert_eq!(below, *tile, "at {pos:?}");
+                }
+
+                // four columns cannot hold a whole number of stripes, so the stripes do not meet across the edge
+                let result = builder(4, seed)
+                    .with_compiled_constraints(compiled)
+                    .build()
+                    .and_then(|wfc| wfc.collapse());
+
+                assert!(matches!(result, Err(WfcError::CellHasZeroEntropy(_))));
+            }
+        }
+    }
+
+    #[test]
+    fn hex_grids_wrapping_with_odd_heights_are_rejected() {
+        for (height, valid) in [(3, false), (4, true)] {
+            let result = WfcBuilder::<u8, BitCell, HexGrid>::new_hex(4, height, vec![0, 1])
+                .with_wrapping(true, true)
+                .build();
+
+            match valid {
+                true => assert!(result.is_ok()),
+                false => assert!(matches!(result, Err(WfcError::InvalidTopology(_)))),
+            }
         }
     }
 }
@@ -0,0 +1,162 @@
+use crate::{WfcError, cell::PossibleIndices};
+use pad::position::Position;
+use std::{cell::RefCell, rc::Rc};
+
+/// Gets notified about the events happening while the [crate::Wfc] collapses. Can be used
+/// for things like progress bars, logging or collecting statistics.
+///
+/// Every method has an empty default implementation, so only the relevant events must be handled.
+/// To access the observer after the collapse, register it wrapped in an [Rc] and [RefCell].
+///
+/// The positions are the ones of the [crate::topology::Topology] of the wfc, which is [Position] for grids.
+pub trait Observer<T, P = Position> {
+    /// Called when a position was collapsed to the given tile, either by the wfc choosing the tile
+    /// or by the user via [crate::Wfc::collapse_tiles].
+    fn cell_collapsed(
+        &mut self,
+        _position: P,
+        _tile: &T,
+    ) {
+    }
+
+    /// Called when tiles were removed from the possible tiles at a position during the propagation.
+    /// The removed tiles are given as indices of the tiles provided to the [crate::WfcBuilder].
+    fn possibilities_removed(
+        &mut self,
+        _position: P,
+        _removed_indices: &[u16],
+    ) {
+    }
+
+    /// Called when no tile is possible at the given position anymore.
+    fn contradiction_found(
+        &mut self,
+        _position: P,
+    ) {
+    }
+
+    /// Called when the wfc is done, either because the board is fully collapsed or because of an error.
+    fn finished(
+        &mut self,
+        _result: Result<(), &WfcError<P>>,
+    ) {
+    }
+}
+
+impl<T, P, O: Observer<T, P>> Observer<T, P> for Rc<RefCell<O>> {
+    fn cell_collapsed(
+        &mut self,
+        position: P,
+        tile: &T,
+    ) {
+        self.borrow_mut().cell_collapsed(position, tile)
+    }
+
+    fn possibilities_removed(
+        &mut self,
+        position: P,
+        removed_indices: &[u16],
+    ) {
+        self.borrow_mut()
+            .possibilities_removed(position, removed_indices)
+    }
+
+    fn contradiction_found(
+        &mut self,
+        position: P,
+    ) {
+        self.borrow_mut().contradiction_found(position)
+    }
+
+    fn finished(
+        &mut self,
+        result: Result<(), &WfcError<P>>,
+    ) {
+        self.borrow_mut().finished(result)
+    }
+}
+
+/// Wrapper around the list of [Observer]s which are registered in the [crate::Wfc].
+pub(crate) struct Observers<T, P> {
+    observers: Vec<Box<dyn Observer<T, P>>>,
+    /// The preallocated Vec of indices which were removed from a cell
+    removed_indices: Vec<u16>,
+}
+
+impl<T, P> Default for Observers<T, P> {
+    fn default() -> Self {
+        Observers {
+            observers: vec![],
+            removed_indices: vec![],
+        }
+    }
+}
+
+impl<T, P: Copy> Observers<T, P> {
+    pub(crate) fn add_observer(
+        &mut self,
+        observer: impl Observer<T, P> + 'static,
+    ) {
+        self.observers.push(Box::new(observer));
+    }
+
+    pub(crate) fn cell_collapsed(
+        &mut self,
+        position: P,
+        tile: &T,
+    ) {
+        for observer in self.observers.iter_mut() {
+            observer.cell_collapsed(position, tile);
+        }
+    }
+
+    /// Notify the observers about the indices which are in `old_indices`, but not in `new_indices` anymore.
+    pub(crate) fn possibilities_removed(
+        &mut self,
+        position: P,
+        old_indices: PossibleIndices,
+        new_indices: &[u16],
+    ) {
+        // avoid collecting the removed indices if no one is interested
+        if self.observers.is_empty() {
+            return;
+        }
+
+        self.removed_indices.clear();
+        self.removed_indices
+            .extend(old_indices.iter().filter(|i| !new_indices.contains(i)));
+
+        for observer in self.observers.iter_mut() {
+            observer.possibilities_removed(position, &self.removed_indices);
+        }
+    }
+
+    /// Notify the observers about a single index which was removed.
+    pub(crate) fn index_removed(
+        &mut self,
+        position: P,
+        index: u16,
+    ) {
+        for observer in self.observers.iter_mut() {
+            observer.possibilities_removed(position, &[index]);
+        }
+    }
+
+    pub(crate) fn contradiction_found(
+        &mut self,
+        position: P,
+    ) {
+        for observer in self.observers.iter_mut() {
+            observer.contradiction_found(position);
+        }
+    }
+
+    pub(crate) fn finished(
+        &mut self,
+        result: Result<(), &WfcError<P>>,
+    ) {
+        for observer in self.observers.iter_mut() {
+            observer.finished(result);
+        }
+    }
+}
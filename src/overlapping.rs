@@ -0,0 +1,272 @@
+use crate::{
+    WfcBuilder, cell::Cell, constraints::DirectionalNeighbours, direction::Direction,
+    grid::WfcGrid,
+};
+use pad::p;
+use std::{collections::HashMap, hash::Hash, rc::Rc};
+
+/// A square section of a sample, which is used as tile in the [OverlappingModel].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Pattern<T> {
+    size: usize,
+    /// The tiles of the pattern in row-major order
+    tiles: Rc<[T]>,
+}
+
+impl<T> Pattern<T> {
+    /// Return the width and height of the pattern.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Return the tile of the pattern at the given coordinates.
+    pub fn get(
+        &self,
+        x: usize,
+        y: usize,
+    ) -> &T {
+        &self.tiles[y * self.size + x]
+    }
+
+    /// Return the tile which is placed in the output at the position of this pattern,
+    /// which is its upper left tile.
+    pub fn tile(&self) -> &T {
+        &self.tiles[0]
+    }
+
+    /// Tells if the other pattern could be placed next to this one in the given [Direction],
+    /// which is the case if all overlapping tiles are equal.
+    fn agrees(
+        &self,
+        other: &Pattern<T>,
+        direction: Direction,
+    ) -> bool
+    where
+        T: PartialEq,
+    {
+        let size = self.size as isize;
+        let (dx, dy) = direction.offset();
+
+        for y in dy.max(0)..(size + dy).min(size) {
+            for x in dx.max(0)..(size + dx).min(size) {
+                let own = self.get(x as usize, y as usize);
+                let others = other.get((x - dx) as usize, (y - dy) as usize);
+
+                if own != others {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+impl<T: Clone> Pattern<T> {
+    /// Create a new pattern by calling the given function for every coordinate of it.
+    fn from_fn(
+        size: usize,
+        f: impl Fn(usize, usize) -> T,
+    ) -> Self {
+        Pattern {
+            size,
+            tiles: (0..size)
+                .flat_map(|y| (0..size).map(move |x| (x, y)))
+                .map(|(x, y)| f(x, y))
+                .collect(),
+        }
+    }
+
+    /// Return this pattern rotated clockwise by 90 degrees.
+    fn rotated(&self) -> Self {
+        Pattern::from_fn(self.size, |x, y| self.get(y, self.size - 1 - x).clone())
+    }
+
+    /// Return this pattern mirrored on the vertical axis.
+    fn mirrored(&self) -> Self {
+        Pattern::from_fn(self.size, |x, y| self.get(self.size - 1 - x, y).clone())
+    }
+}
+
+/// The overlapping model of the wave function collapse. Instead of providing the tiles and constraints
+/// manually, they are learned from a sample.
+///
+/// Every square section of the sample with the given size becomes a [Pattern], which is used as a tile
+/// in the [crate::Wfc]. The more often a pattern occurs in the sample, the higher is its weight. Two
+/// patterns can be neighbours if they are equal in their overlapping area. The result therefore locally
+/// resembles the sample.
+pub struct OverlappingModel<T> {
+    patterns: Vec<Pattern<T>>,
+    weights: Vec<f32>,
+    neighbours: DirectionalNeighbours,
+}
+
+impl<T: Clone + Eq + Hash> OverlappingModel<T> {
+    /// Create the [OverlappingModel] by extracting all patterns with the given size from the sample.
+    pub fn new(
+        sample: &WfcGrid<T>,
+        pattern_size: usize,
+    ) -> Self {
+        Self::from_sample(sample, pattern_size, false)
+    }
+
+    /// Create the [OverlappingModel] like [OverlappingModel::new], but also add all rotations and
+    /// reflections of the extracted patterns.
+    pub fn new_with_symmetry(
+        sample: &WfcGrid<T>,
+        pattern_size: usize,
+    ) -> Self {
+        Self::from_sample(sample, pattern_size, true)
+    }
+
+    fn from_sample(
+        sample: &WfcGrid<T>,
+        pattern_size: usize,
+        symmetry: bool,
+    ) -> Self {
+        assert!(
+            pattern_size > 0 && pattern_size <= sample.width() && pattern_size <= sample.height(),
+            "The pattern size should fit into the sample"
+        );
+
+        let mut patterns: Vec<Pattern<T>> = vec![];
+        let mut weights = vec![];
+        // the index of every pattern in `patterns`, to find duplicates quickly
+        let mut indices = HashMap::new();
+
+        for y in 0..=(sample.height() - pattern_size) {
+            for x in 0..=(sample.width() - pattern_size) {
+                let pattern = Pattern::from_fn(pattern_size, |px, py| {
+                    sample[p!(x + px, y + py)].clone()
+                });
+
+                let variants = match symmetry {
+                    true => Self::symmetric_variants(pattern),
+                    false => vec![pattern],
+                };
+
+                for variant in variants {
+                    let index = *indices.entry(variant).or_insert_with_key(|variant| {
+                        patterns.push(variant.clone());
+                        weights.push(0.0);
+                        patterns.len() - 1
+                    });
+                    weights[index] += 1.0;
+                }
+            }
+        }
+
+        let neighbours = DirectionalNeighbours::from_fn(patterns.len(), |i0, dir, i1| {
+            patterns[i0 as usize].agrees(&patterns[i1 as usize], dir)
+        });
+
+        OverlappingModel {
+            neighbours,
+            patterns,
+            weights,
+        }
+    }
+
+    /// Return all rotations and reflections of the given pattern.
+    fn symmetric_variants(pattern: Pattern<T>) -> Vec<Pattern<T>> {
+        let mirrored = pattern.mirrored();
+        let mut variants = vec![pattern, mirrored];
+
+        for i in 0..6 {
+            variants.push(variants[i].rotated());
+        }
+
+        variants
+    }
+
+    /// Return all patterns which were extracted from the sample.
+    pub fn patterns(&self) -> &[Pattern<T>] {
+        &self.patterns
+    }
+
+    /// Return the weights of all patterns, which is the amount of their occurrences in the sample.
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    /// Create a [WfcBuilder] for an output with the given dimensions, which is configured with the patterns,
+    /// weights and neighbour constraints of this model. The result of the wfc can be converted to the
+    /// actual tiles with `grid.map(|pattern| pattern.tile().clone())`.
+    pub fn builder<C: Cell>(
+        &self,
+        width: usize,
+        height: usize,
+    ) -> WfcBuilder<Pattern<T>, C> {
+        WfcBuilder::new(width, height, self.patterns.clone())
+            .with_weights(self.weights.iter().copied())
+            .with_constraint(self.neighbours.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cell::BitCell,
+        grid::WfcGrid,
+        overlapping::{OverlappingModel, Pattern},
+    };
+    use pad::p;
+
+    #[test]
+    fn patterns_are_extracted_from_sample() {
+        #[rustfmt::skip]
+        let sample = WfcGrid::new(3, 3, vec![
+            0, 1, 0,
+            1, 0, 1,
+            0, 1, 0,
+        ]);
+
+        let model = OverlappingModel::new(&sample, 2);
+
+        // the checkerboard only contains two different 2x2 patterns, both twice
+        assert_eq!(model.patterns().len(), 2);
+        assert_eq!(model.weights(), &[2.0, 2.0]);
+        assert_eq!(model.patterns()[0].get(1, 0), &1);
+        assert_eq!(model.patterns()[1].tile(), &1);
+    }
+
+    #[test]
+    fn output_only_contains_patterns_of_the_sample() {
+        #[rustfmt::skip]
+        let sample = WfcGrid::new(6, 6, vec![
+            0, 0, 1, 0, 0, 1,
+            0, 2, 1, 0, 0, 1,
+            1, 1, 1, 1, 1, 1,
+            0, 0, 1, 0, 2, 1,
+            0, 0, 1, 0, 0, 1,
+            1, 1, 1, 1, 1, 1,
+        ]);
+        let size = 2;
+
+        for symmetry in [false, true] {
+            let model = match symmetry {
+                true => OverlappingModel::new_with_symmetry(&sample, size),
+                false => OverlappingModel::new(&sample, size),
+            };
+
+            for seed in 0..10 {
+                let output = model
+                    .builder::<BitCell>(8, 8)
+                    .with_backtracking(20, 1000)
+                    .with_seed(seed)
+                    .build()
+                    .unwrap()
+                    .collapse()
+                    .unwrap()
+                    .map(|pattern| *pattern.tile());
+
+                for y in 0..=(output.height() - size) {
+                    for x in 0..=(output.width() - size) {
+                        let window = Pattern::from_fn(size, |px, py| output[p!(x + px, y + py)]);
+                        assert!(model.patterns().contains(&window));
+                    }
+                }
+            }
+        }
+    }
+}
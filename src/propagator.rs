@@ -0,0 +1,84 @@
+use crate::{bitmask::tiles_in, constraints::DirectionalNeighbours, direction::NeighbourDirection};
+use std::marker::PhantomData;
+use std::ops::Range;
+
+/// The compiled form of constraints which only decide what tiles can be next to each other.
+///
+/// It is used to propagate removed tiles with the AC-4 algorithm: for every direction, every tile in a
+/// cell counts the possible tiles of the neighbour which allow it (its supports). Removing a tile only
+/// decrements the supports of its compatible tiles in the neighbour cells, and a tile is only removed when
+/// one of its supports drops to zero. No constraint has to be evaluated during the propagation.
+#[derive(Clone, Debug)]
+pub(crate) struct AdjacencyPropagator<D> {
+    /// For every tile and direction, the tiles which can be the neighbour in this direction.
+    /// See [AdjacencyPropagator::index] for the layout.
+    compatible: Vec<Vec<u16>>,
+    _direction: PhantomData<D>,
+}
+
+impl<D: NeighbourDirection> AdjacencyPropagator<D> {
+    /// Compile the given rules, which must all be fulfilled, into a compatibility table.
+    pub fn new(
+        num_tiles: usize,
+        rules: &[DirectionalNeighbours<D>],
+    ) -> Self {
+        let mut compatible = Vec::with_capacity(num_tiles * D::ALL.len());
+        let mut allowed = vec![0; num_tiles.div_ceil(u128::BITS as usize)];
+
+        for tile in 0..num_tiles as u16 {
+            for dir in D::ALL.iter().copied() {
+                // the neighbours which are allowed by all rules
+                allowed.fill(u128::MAX);
+
+                for rule in rules {
+                    for (word, rule_word) in allowed.iter_mut().zip(rule.allowed_mask(tile, dir)) {
+                        *word &= rule_word;
+                    }
+                }
+
+                compatible.push(
+                    tiles_in(&allowed)
+                        .take_while(|neighbour| (*neighbour as usize) < num_tiles)
+                        .collect(),
+                );
+            }
+        }
+
+        AdjacencyPropagator {
+            compatible,
+            _direction: PhantomData,
+        }
+    }
+
+    /// Return the index of a tile and direction in a table which has an entry for every tile and direction.
+    /// Such a table for a single cell has a size of `num_tiles * D::ALL.len()`.
+    pub fn index(
+        tile: u16,
+        direction: D,
+    ) -> usize {
+        tile as usize * D::ALL.len() + direction.index()
+    }
+
+    /// Return the range of the entries of all directions of the given tile in such a table.
+    pub fn tile_entries(tile: u16) -> Range<usize> {
+        let start = tile as usize * D::ALL.len();
+        start..(start + D::ALL.len())
+    }
+
+    /// Return the tiles which can be the neighbour of the given tile in the given direction.
+    pub fn compatible(
+        &self,
+        tile: u16,
+        direction: D,
+    ) -> &[u16] {
+        &self.compatible[Self::index(tile, direction)]
+    }
+
+    /// Return the supports of every tile and direction in a fresh cell, where every tile is possible.
+    pub fn initial_supports(&self) -> Vec<u16> {
+        self.compatible
+            .iter()
+            .map(|compatible| compatible.len() as u16)
+            .collect()
+    }
+}
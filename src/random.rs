@@ -1,6 +1,6 @@
 use crate::cell::PossibleIndices;
 use rand::{
-    SeedableRng,
+    Rng, SeedableRng,
     distributions::WeightedIndex,
     prelude::{Distribution, StdRng},
 };
@@ -9,31 +9,52 @@ use std::hash::{DefaultHasher, Hash, Hasher};
 /// Provides random numbers to the WFC.
 pub struct Random {
     rng: StdRng,
+    /// The seed the rng was created with. Used to derive the seeds for retries.
+    seed: u64,
 }
 
 impl Random {
     pub fn new() -> Self {
-        Random {
-            rng: StdRng::from_entropy(),
-        }
+        Self::from_u64(rand::random())
     }
 
     pub fn from_seed(seed: impl Hash) -> Self {
         let mut hasher = DefaultHasher::new();
         seed.hash(&mut hasher);
-        let seed = hasher.finish();
+        Self::from_u64(hasher.finish())
+    }
 
+    fn from_u64(seed: u64) -> Self {
         Random {
             rng: StdRng::seed_from_u64(seed),
+            seed,
         }
     }
 
+    /// Reset the rng for the given attempt of the wfc. The new seed is the original seed plus
+    /// the attempt, so every attempt is reproducible from the original seed.
+    pub fn reseed(
+        &mut self,
+        attempt: usize,
+    ) {
+        self.rng = StdRng::seed_from_u64(self.seed.wrapping_add(attempt as u64));
+    }
+
+    /// Return a random number which decides between otherwise equal choices.
+    pub fn tie_breaker(&mut self) -> u32 {
+        self.rng.r#gen()
+    }
+
+    /// Choose one of the given choices, where the chance of every choice is its given weight.
+    ///
+    /// Returns None if no choice can be made, because a weight is negative or not finite, or all of them are zero.
     pub fn choose_weighted(
         &mut self,
         weights: impl IntoIterator<Item = f32>,
         choices: PossibleIndices,
-    ) -> u8 {
-        let dist = WeightedIndex::new(weights).unwrap();
-        choices.get(dist.sample(&mut self.rng))
+    ) -> Option<u16> {
+        WeightedIndex::new(weights)
+            .ok()
+            .map(|dist| choices.get(dist.sample(&mut self.rng)))
     }
 }
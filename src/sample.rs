@@ -0,0 +1,110 @@
+use crate::{
+    WfcBuilder, cell::Cell, constraints::Constraint, direction::Direction, grid::WfcGrid,
+};
+use pad::p;
+use std::collections::BTreeMap;
+
+/// The tiles, weights and neighbour constraint which were learned from a sample, for example
+/// with [crate::constraints::DirectionalNeighbours::from_sample].
+pub struct SampleRules<T, N> {
+    /// All different tiles of the sample, in the order of their first occurrence
+    pub tiles: Vec<T>,
+    /// The weight of every tile, which is the amount of its occurrences in the sample
+    pub weights: Vec<f32>,
+    /// How often a tile had another tile as neighbour in the given direction. The tiles are
+    /// given as indices of `tiles`.
+    pub neighbour_counts: Vec<(u16, Direction, u16, usize)>,
+    /// The [Constraint] which allows all neighbours found in the sample
+    pub constraint: N,
+}
+
+impl<T: Clone + PartialEq, N> SampleRules<T, N> {
+    /// Scan the given sample and create the constraint from the amount of tiles and the neighbour counts.
+    pub(crate) fn learn(
+        sample: &WfcGrid<T>,
+        create_constraint: impl FnOnce(usize, &[(u16, Direction, u16, usize)]) -> N,
+    ) -> Self {
+        let mut tiles: Vec<T> = vec![];
+        let mut weights: Vec<f32> = vec![];
+
+        let indices = sample
+            .iter()
+            .map(|(_, tile)| match tiles.iter().position(|t| t == tile) {
+                Some(index) => {
+                    weights[index] += 1.0;
+                    index as u16
+                }
+                None => {
+                    tiles.push(tile.clone());
+                    weights.push(1.0);
+                    (tiles.len() - 1) as u16
+                }
+            })
+            .collect::<Vec<_>>();
+        let indices = WfcGrid::new(sample.width(), sample.height(), indices);
+
+        let mut counts = BTreeMap::<(u16, Direction, u16), usize>::new();
+
+        for (pos, index) in indices.iter() {
+            for dir in Direction::ALL {
+                let (dx, dy) = dir.offset();
+
+                if let Some(neighbour) = indices.get(p!(pos.x + dx, pos.y + dy)) {
+                    *counts.entry((*index, dir, *neighbour)).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let neighbour_counts = counts
+            .into_iter()
+            .map(|((index, dir, neighbour), count)| (index, dir, neighbour, count))
+            .collect::<Vec<_>>();
+
+        SampleRules {
+            constraint: create_constraint(tiles.len(), &neighbour_counts),
+            tiles,
+            weights,
+            neighbour_counts,
+        }
+    }
+}
+
+impl<T, N> SampleRules<T, N>
+where
+    T: Clone,
+    N: Constraint<T> + Clone + 'static,
+{
+    /// Create a [WfcBuilder] with the given dimensions, which is configured with the tiles, weights
+    /// and constraint learned from the sample.
+    pub fn builder<C: Cell>(
+        &self,
+        width: usize,
+        height: usize,
+    ) -> WfcBuilder<T, C> {
+        WfcBuilder::new(width, height, self.tiles.clone())
+            .with_weights(self.weights.iter().copied())
+            .with_constraint(self.constraint.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{constraints::DirectionalNeighbours, direction::Direction::*, grid::WfcGrid};
+
+    #[test]
+    fn rules_are_learned_from_sample() {
+        let sample = WfcGrid::new(3, 1, vec!['a', 'b', 'b']);
+
+        let rules = DirectionalNeighbours::from_sample(&sample);
+
+        assert_eq!(rules.tiles, vec!['a', 'b']);
+        assert_eq!(rules.weights, vec![1.0, 2.0]);
+        assert_eq!(
+            rules.neighbour_counts,
+            vec![(0, Right, 1, 1), (1, Left, 0, 1), (1, Left, 1, 1), (1, Right, 1, 1)]
+        );
+        assert!(rules.constraint.allowed(0, Right, 1));
+        assert!(!rules.constraint.allowed(0, Left, 1));
+        assert!(!rules.constraint.allowed(0, Right, 0));
+    }
+}
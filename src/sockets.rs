@@ -0,0 +1,113 @@
+use crate::direction::Direction;
+
+/// The socket on the edge of a tile. Two tiles can be neighbours if their facing sockets fit together.
+///
+/// Most sockets are symmetric, which means they look the same no matter from which side they are seen.
+/// Symmetric sockets fit to symmetric sockets with the same label. Asymmetric sockets (like a road which
+/// is not centered on the edge) only fit to the flipped version of themselves, as the facing edge of the
+/// neighbour is seen mirrored.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Socket<L> {
+    /// A socket which only fits to a symmetric socket with the same label.
+    Symmetric(L),
+    /// An asymmetric socket which only fits to the flipped socket with the same label.
+    Asymmetric(L),
+    /// The mirrored version of an asymmetric socket, which only fits to the asymmetric socket with the same label.
+    Flipped(L),
+}
+
+impl<L: PartialEq> Socket<L> {
+    /// Tells if this socket fits to the given socket of a facing edge.
+    pub fn fits(
+        &self,
+        other: &Socket<L>,
+    ) -> bool {
+        match (self, other) {
+            (Socket::Symmetric(a), Socket::Symmetric(b)) => a == b,
+            (Socket::Asymmetric(a), Socket::Flipped(b)) => a == b,
+            (Socket::Flipped(a), Socket::Asymmetric(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Return the mirrored version of this socket.
+    pub fn flipped(self) -> Socket<L> {
+        match self {
+            Socket::Symmetric(l) => Socket::Symmetric(l),
+            Socket::Asymmetric(l) => Socket::Flipped(l),
+            Socket::Flipped(l) => Socket::Asymmetric(l),
+        }
+    }
+}
+
+/// A tile which declares a [Socket] for each of its edges. Used to derive the allowed neighbours of
+/// all tiles automatically, see [crate::constraints::DirectionalNeighbours::from_sockets].
+///
+/// The edges are given by the directions of the [crate::topology::Topology], so a hexagonal tile implements
+/// `Sockets<HexDirection>` and declares six sockets.
+pub trait Sockets<D = Direction> {
+    /// The label of the sockets, for example a color or a name.
+    type Label: PartialEq;
+
+    /// Return the [Socket] on the edge of this tile in the given direction.
+    fn socket(
+        &self,
+        direction: D,
+    ) -> Socket<Self::Label>;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        constraints::DirectionalNeighbours,
+        direction::Direction,
+        sockets::{Socket, Socket::*, Sockets},
+    };
+
+    /// A tile with the sockets in the order of [Direction::ALL].
+    struct Tile([Socket<u8>; 4]);
+
+    impl Sockets for Tile {
+        type Label = u8;
+
+        fn socket(
+            &self,
+            direction: Direction,
+        ) -> Socket<u8> {
+            self.0[direction as usize]
+        }
+    }
+
+    #[test]
+    fn sockets_fit() {
+        assert!(Symmetric(1).fits(&Symmetric(1)));
+        assert!(!Symmetric(1).fits(&Symmetric(2)));
+        assert!(Asymmetric(1).fits(&Flipped(1)));
+        assert!(Flipped(1).fits(&Asymmetric(1)));
+        assert!(!Asymmetric(1).fits(&Asymmetric(1)));
+        assert!(!Symmetric(1).fits(&Flipped(1)));
+    }
+
+    #[test]
+    fn asymmetric_sockets_only_fit_their_flipped_partner() {
+        // a road leaves a to the right, b takes it from the left, and c has the road on the wrong side
+        let tiles = [
+            Tile([Symmetric(0), Symmetric(0), Symmetric(2), Asymmetric(1)]),
+            Tile([Symmetric(0), Symmetric(0), Flipped(1), Symmetric(3)]),
+            Tile([Symmetric(0), Symmetric(0), Asymmetric(1), Symmetric(3)]),
+        ];
+
+        let from_sockets = DirectionalNeighbours::from_sockets(&tiles);
+        let from_fn = DirectionalNeighbours::from_socket_fn(&tiles, |tile, dir| tile.socket(dir));
+
+        for neighbours in [from_sockets, from_fn] {
+            assert!(neighbours.allowed(0, Direction::Right, 1));
+            assert!(neighbours.allowed(1, Direction::Left, 0));
+            assert!(!neighbours.allowed(1, Direction::Right, 0));
+            assert!(!neighbours.allowed(0, Direction::Left, 1));
+            assert!(!neighbours.allowed(0, Direction::Right, 2));
+            assert!(!neighbours.allowed(2, Direction::Left, 0));
+            assert!(neighbours.allowed(0, Direction::Down, 2));
+        }
+    }
+}
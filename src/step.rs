@@ -0,0 +1,58 @@
+use crate::{
+    Wfc, WfcError,
+    cell::Cell,
+    topology::{SquareGrid, Topology},
+};
+use pad::position::Position;
+
+/// Describes a single step of the [Wfc], which is the collapse of one position and the
+/// propagation of this collapse to the rest of the board.
+#[derive(Clone, Debug)]
+pub struct Step<T, P = Position> {
+    /// The position which was collapsed in this step.
+    pub position: P,
+    /// The tile the position was collapsed to.
+    pub tile: T,
+    /// All positions besides `position` whose possible tiles shrank due to this step, ordered by their cell
+    /// index. Every position is listed once.
+    pub changed_positions: Vec<P>,
+    /// Tells if the step led to a contradiction which was resolved by backtracking. In this case,
+    /// the board was reset to an earlier state, so the collapse of this step did not persist and
+    /// positions outside of `changed_positions` might have changed as well.
+    pub backtracked: bool,
+}
+
+/// An [Iterator] which performs one [Step] of the [Wfc] per call to [Iterator::next].
+/// It ends if the board is fully collapsed or after a contradiction was returned.
+pub struct Steps<'a, T: Clone, C: Cell, G: Topology = SquareGrid> {
+    wfc: &'a mut Wfc<T, C, G>,
+    done: bool,
+}
+
+impl<'a, T: Clone, C: Cell, G: Topology> Steps<'a, T, C, G> {
+    pub(crate) fn new(wfc: &'a mut Wfc<T, C, G>) -> Self {
+        Steps { wfc, done: false }
+    }
+}
+
+impl<T: Clone, C: Cell, G: Topology> Iterator for Steps<'_, T, C, G> {
+    type Item = Result<Step<T, G::Position>, WfcError<G::Position>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.wfc.step() {
+            Ok(Some(step)) => Some(Ok(step)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(error) => {
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{
+    direction::Direction,
+    sockets::{Socket, Sockets},
+};
+
+/// The symmetry class of a tile, like in the classic simple tiled model. It tells which
+/// rotations and reflections of a tile look different from each other.
+///
+/// The classes assume a specific base orientation of the tile, which is described by the
+/// box drawing character next to each class.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Symmetry {
+    /// Looks the same in every orientation, like `┼`.
+    X,
+    /// Looks the same after a rotation of 180 degrees or a reflection, like `│`.
+    I,
+    /// Looks the same after a rotation of 180 degrees, a reflection equals a rotation by 90 degrees, like `\`.
+    Diagonal,
+    /// A reflection equals a rotation by 270 degrees, like `└`.
+    L,
+    /// Looks the same after a reflection, like `┬`.
+    T,
+    /// Every rotation and reflection looks different.
+    F,
+}
+
+impl Symmetry {
+    /// Return the canonical version of the given [Orientation]. All orientations which look the
+    /// same for a tile of this class have the same canonical orientation.
+    pub fn canonical(
+        self,
+        orientation: Orientation,
+    ) -> Orientation {
+        let Orientation {
+            rotation,
+            reflected,
+        } = orientation;
+
+        match self {
+            Symmetry::X => Orientation::IDENTITY,
+            Symmetry::I => Orientation::new(rotation % 2, false),
+            Symmetry::Diagonal => Orientation::new((rotation + reflected as u8) % 2, false),
+            Symmetry::L if reflected => Orientation::new(rotation + 3, false),
+            Symmetry::L | Symmetry::T => Orientation::new(rotation, false),
+            Symmetry::F => orientation,
+        }
+    }
+
+    /// Return all [Orientation]s which look different for a tile of this class.
+    pub fn orientations(self) -> impl Iterator<Item = Orientation> {
+        Orientation::ALL
+            .into_iter()
+            .filter(move |o| self.canonical(*o) == *o)
+    }
+}
+
+/// A tile which belongs to a [Symmetry] class. Such tiles can be expanded to all their
+/// different rotations and reflections, see [Oriented::expand].
+pub trait Symmetric {
+    /// Return the [Symmetry] class of this tile.
+    fn symmetry(&self) -> Symmetry;
+}
+
+/// The rotation and reflection of a tile, relative to its base orientation. The tile is first mirrored
+/// on the vertical axis (if reflected) and then rotated clockwise.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Orientation {
+    /// The amount of clockwise rotations by 90 degrees, from 0 to 3
+    pub rotation: u8,
+    /// Tells if the tile is mirrored on the vertical axis
+    pub reflected: bool,
+}
+
+impl Orientation {
+    /// The base orientation of a tile.
+    pub const IDENTITY: Orientation = Orientation::new(0, false);
+
+    /// All possible [Orientation]s.
+    pub const ALL: [Orientation; 8] = [
+        Orientation::new(0, false),
+        Orientation::new(1, false),
+        Orientation::new(2, false),
+        Orientation::new(3, false),
+        Orientation::new(0, true),
+        Orientation::new(1, true),
+        Orientation::new(2, true),
+        Orientation::new(3, true),
+    ];
+
+    pub const fn new(
+        rotation: u8,
+        reflected: bool,
+    ) -> Self {
+        Orientation {
+            rotation: rotation % 4,
+            reflected,
+        }
+    }
+
+    /// Return the [Orientation] which results from applying this orientation first and the other one afterward.
+    pub fn then(
+        self,
+        other: Orientation,
+    ) -> Orientation {
+        // mirroring after a rotation equals the opposite rotation before mirroring
+        let rotation = match other.reflected {
+            true => 4 - self.rotation,
+            false => self.rotation,
+        };
+
+        Orientation::new(other.rotation + rotation, self.reflected ^ other.reflected)
+    }
+
+    /// Return the [Orientation] which reverts this orientation.
+    pub fn inverse(self) -> Orientation {
+        match self.reflected {
+            // a reflection followed by a rotation reverts itself
+            true => self,
+            false => Orientation::new(4 - self.rotation, false),
+        }
+    }
+
+    /// Return the [Direction] which results from applying this orientation to the given one.
+    pub fn apply(
+        self,
+        direction: Direction,
+    ) -> Direction {
+        let mut direction = match self.reflected {
+            true => direction.mirrored(),
+            false => direction,
+        };
+
+        for _ in 0..self.rotation {
+            direction = direction.rotated_clockwise();
+        }
+
+        direction
+    }
+}
+
+/// A variant of a [Symmetric] tile with a specific [Orientation].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Oriented<T> {
+    pub tile: T,
+    /// The orientation of the tile, which is always the canonical one for its [Symmetry]
+    pub orientation: Orientation,
+}
+
+impl<T: Symmetric> Oriented<T> {
+    pub fn new(
+        tile: T,
+        orientation: Orientation,
+    ) -> Self {
+        let orientation = tile.symmetry().canonical(orientation);
+        Oriented { tile, orientation }
+    }
+
+    /// Return this variant after applying the given [Orientation] to it.
+    pub fn transformed(
+        &self,
+        orientation: Orientation,
+    ) -> Self
+    where
+        T: Clone,
+    {
+        Oriented::new(self.tile.clone(), self.orientation.then(orientation))
+    }
+
+    /// Expand the given tiles to all their different variants. The variants of a tile are next to
+    /// each other, starting with the base orientation.
+    pub fn expand(tiles: impl IntoIterator<Item = T>) -> Vec<Oriented<T>>
+    where
+        T: Clone,
+    {
+        tiles
+            .into_iter()
+            .flat_map(|tile| {
+                tile.symmetry()
+                    .orientations()
+                    .map(move |o| Oriented::new(tile.clone(), o))
+            })
+            .collect()
+    }
+}
+
+impl<T: Symmetric> From<T> for Oriented<T> {
+    fn from(tile: T) -> Self {
+        Oriented::new(tile, Orientation::IDENTITY)
+    }
+}
+
+impl<T: Sockets> Sockets for Oriented<T> {
+    type Label = T::Label;
+
+    fn socket(
+        &self,
+        direction: Direction,
+    ) -> Socket<Self::Label> {
+        // the edge in the given direction was moved there from the base tile
+        let socket = self
+            .tile
+            .socket(self.orientation.inverse().apply(direction));
+
+        match self.orientation.reflected {
+            true => socket.flipped(),
+            false => socket,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        constraints::DirectionalNeighbours,
+        direction::Direction,
+        symmetry::{Orientation, Oriented, Symmetric, Symmetry},
+    };
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Tile {
+        Corner,
+        Line,
+        Tee,
+    }
+
+    impl Symmetric for Tile {
+        fn symmetry(&self) -> Symmetry {
+            match self {
+                Tile::Corner => Symmetry::L,
+                Tile::Line => Symmetry::I,
+                Tile::Tee => Symmetry::T,
+            }
+        }
+    }
+
+    #[test]
+    fn symmetry_classes_have_correct_orientations() {
+        let counts = [
+            Symmetry::X,
+            Symmetry::I,
+            Symmetry::Diagonal,
+            Symmetry::L,
+            Symmetry::T,
+            Symmetry::F,
+        ]
+        .map(|s| s.orientations().count());
+
+        assert_eq!(counts, [1, 2, 2, 4, 4, 8]);
+    }
+
+    #[test]
+    fn orientations_compose() {
+        for a in Orientation::ALL {
+            assert_eq!(a.then(a.inverse()), Orientation::IDENTITY);
+
+            for b in Orientation::ALL {
+                for dir in Direction::ALL {
+                    assert_eq!(a.then(b).apply(dir), b.apply(a.apply(dir)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rules_are_applied_to_all_orientations() {
+        let tiles = Oriented::expand([Tile::Corner, Tile::Line, Tile::Tee]);
+        let rules = [
+            (Tile::Corner, Direction::Right, Tile::Line),
+            (Tile::Tee, Direction::Down, Tile::Line),
+        ];
+        let neighbours = DirectionalNeighbours::new_with_symmetry(rules, &tiles);
+
+        let index = |tile, rotation, reflected| {
+            let variant = Oriented::new(tile, Orientation::new(rotation, reflected));
+            tiles.iter().position(|t| *t == variant).unwrap() as u16
+        };
+
+        assert_eq!(tiles.len(), 10);
+
+        for (t0, dir, t1) in rules {
+            for o in Orientation::ALL {
+                let (r, m) = (o.rotation, o.reflected);
+                assert!(neighbours.allowed(index(t0, r, m), o.apply(dir), index(t1, r, m)));
+            }
+        }
+
+        // a line rotated by 180 degrees is the same line, and a reflected corner is a rotated one
+        let corner = |rotation| index(Tile::Corner, rotation, false);
+        let line = |rotation| index(Tile::Line, rotation, false);
+        assert!(neighbours.allowed(corner(2), Direction::Left, line(0)));
+        assert!(neighbours.allowed(corner(3), Direction::Left, line(0)));
+        assert!(neighbours.allowed(corner(0), Direction::Up, line(1)));
+        assert!(!neighbours.allowed(corner(0), Direction::Up, line(0)));
+        assert!(!neighbours.allowed(index(Tile::Tee, 0, false), Direction::Right, line(0)));
+    }
+}
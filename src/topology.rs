@@ -0,0 +1,802 @@
+use crate::WfcError;
+use crate::direction::{Direction, HexDirection, NeighbourDirection, VoxelDirection};
+use crate::grid::{WfcGrid, WfcVolume};
+use pad::p;
+use pad::position::Position;
+use std::fmt::Debug;
+
+/// Describes the shape of the board: which cells exist, where they are and which cells are neighbours.
+///
+/// The cells are identified by an index from 0 to [Topology::num_cells], which is how the board stores them.
+/// Every neighbour of a cell is labelled with the [NeighbourDirection] it lies in. If a cell b is the neighbour
+/// of a cell a in direction d, then a must be the neighbour of b in the opposite direction of d. A cell can
+/// have several neighbours in the same direction, see [Topology::unique_directions].
+pub trait Topology {
+    /// The coordinates of a cell, which are used to talk about cells outside the board.
+    type Position: Copy + Debug + PartialEq;
+    /// The directions the neighbours of a cell can lie in.
+    type Direction: NeighbourDirection;
+    /// The result of a collapse, which holds a tile for every cell.
+    type Grid<T>;
+
+    /// Return the amount of cells.
+    fn num_cells(&self) -> usize;
+
+    /// Return the position of the cell with the given index.
+    fn position(
+        &self,
+        cell: usize,
+    ) -> Self::Position;
+
+    /// Return the index of the cell at the given position, or None if no cell exists there.
+    fn cell(
+        &self,
+        position: Self::Position,
+    ) -> Option<usize>;
+
+    /// Return the indices of all neighbours of the given cell, alongside the direction they lie in.
+    fn neighbours(
+        &self,
+        cell: usize,
+    ) -> impl Iterator<Item = (Self::Direction, usize)>;
+
+    /// Create the result of a collapse from the tile of every cell, ordered by the cell indices.
+    fn grid<T>(
+        &self,
+        tiles: Vec<T>,
+    ) -> Self::Grid<T>;
+
+    /// Tells if every cell has at most one neighbour in every direction. Only then the constraints can be
+    /// propagated with the faster AC-4 algorithm,
+    /// see [crate::constraints::Constraint::as_directional_neighbours].
+    fn unique_directions(&self) -> bool {
+        true
+    }
+
+    /// Check if the topology describes a valid board. This is done when the [crate::Wfc] is built,
+    /// so invalid settings are reported as [WfcError::InvalidTopology].
+    fn validate(&self) -> Result<(), WfcError<Self::Position>> {
+        Ok(())
+    }
+}
+
+/// A rectangular grid, where every cell has up to four neighbours in the cardinal [Direction]s.
+/// This is the default [Topology] of the [crate::Wfc]. The cells are indexed in row-major order.
+///
+/// The grid can wrap around on every axis (see [SquareGrid::with_wrapping]), so the cells on opposite edges
+/// are neighbours and the result can be tiled seamlessly.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SquareGrid {
+    width: usize,
+    height: usize,
+    wrap_x: bool,
+    wrap_y: bool,
+}
+
+impl SquareGrid {
+    pub fn new(
+        width: usize,
+        height: usize,
+    ) -> Self {
+        SquareGrid {
+            width,
+            height,
+            wrap_x: false,
+            wrap_y: false,
+        }
+    }
+
+    /// Set if the grid wraps around on the x axis (the left and right edges are neighbours)
+    /// and on the y axis (the top and bottom edges are neighbours).
+    pub fn with_wrapping(
+        mut self,
+        wrap_x: bool,
+        wrap_y: bool,
+    ) -> Self {
+        self.wrap_x = wrap_x;
+        self.wrap_y = wrap_y;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Return the position of the neighbour in the given [Direction] of the given position, wrapped around
+    /// the edges of the wrapping axes. Might be outside the grid on the other axes.
+    pub fn neighbour(
+        &self,
+        position: Position,
+        direction: Direction,
+    ) -> Position {
+        let (dx, dy) = direction.offset();
+        wrap(
+            (self.width, self.wrap_x),
+            (self.height, self.wrap_y),
+            p!(position.x + dx, position.y + dy),
+        )
+    }
+}
+
+impl Topology for SquareGrid {
+    type Position = Position;
+    type Direction = Direction;
+    type Grid<T> = WfcGrid<T>;
+
+    fn num_cells(&self) -> usize {
+        self.width * self.height
+    }
+
+    fn position(
+        &self,
+        cell: usize,
+    ) -> Position {
+        p!(cell % self.width, cell / self.width)
+    }
+
+    fn cell(
+        &self,
+        position: Position,
+    ) -> Option<usize> {
+        grid_cell(self.width, self.height, position)
+    }
+
+    fn neighbours(
+        &self,
+        cell: usize,
+    ) -> impl Iterator<Item = (Direction, usize)> {
+        let position = self.position(cell);
+
+        Direction::ALL.into_iter().filter_map(move |dir| {
+            self.cell(self.neighbour(position, dir))
+                .map(|neighbour| (dir, neighbour))
+        })
+    }
+
+    fn grid<T>(
+        &self,
+        tiles: Vec<T>,
+    ) -> WfcGrid<T> {
+        WfcGrid::new(self.width, self.height, tiles)
+    }
+}
+
+/// A grid of hexagons with pointy tops, where every cell has up to six neighbours in the [HexDirection]s.
+///
+/// The hexagons are addressed with offset coordinates: x is the column and y the row, and every odd row is
+/// shifted half a hexagon to the right. Use [HexGrid::to_axial] and [HexGrid::from_axial] to convert them to
+/// axial coordinates. The result of a collapse is a [WfcGrid] in offset coordinates.
+///
+/// Like the [SquareGrid], the grid can wrap around on every axis, see [HexGrid::with_wrapping].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct HexGrid {
+    width: usize,
+    height: usize,
+    wrap_x: bool,
+    wrap_y: bool,
+}
+
+impl HexGrid {
+    pub fn new(
+        width: usize,
+        height: usize,
+    ) -> Self {
+        HexGrid {
+            width,
+            height,
+            wrap_x: false,
+            wrap_y: false,
+        }
+    }
+
+    /// Set if the grid wraps around on the x axis (the left and right edges are neighbours)
+    /// and on the y axis (the top and bottom edges are neighbours).
+    ///
+    /// A grid which wraps on the y axis must have an even height, otherwise [Topology::validate] fails.
+    /// The first and the last row would both be unshifted, so their hexagons could not fit together.
+    pub fn with_wrapping(
+        mut self,
+        wrap_x: bool,
+        wrap_y: bool,
+    ) -> Self {
+        self.wrap_x = wrap_x;
+        self.wrap_y = wrap_y;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Return the position of the neighbour in the given [HexDirection] of the given position, wrapped around
+    /// the edges of the wrapping axes. Might be outside the grid on the other axes.
+    pub fn neighbour(
+        &self,
+        position: Position,
+        direction: HexDirection,
+    ) -> Position {
+        let (dx, dy) = direction.offset(position.y & 1 == 1);
+        wrap(
+            (self.width, self.wrap_x),
+            (self.height, self.wrap_y),
+            p!(position.x + dx, position.y + dy),
+        )
+    }
+
+    /// Convert the given offset coordinates to axial coordinates (q, r).
+    pub fn to_axial(position: Position) -> (isize, isize) {
+        (position.x - (position.y - (position.y & 1)) / 2, position.y)
+    }
+
+    /// Convert the given axial coordinates (q, r) to offset coordinates.
+    pub fn from_axial(
+        q: isize,
+        r: isize,
+    ) -> Position {
+        p!(q + (r - (r & 1)) / 2, r)
+    }
+}
+
+impl Topology for HexGrid {
+    type Position = Position;
+    type Direction = HexDirection;
+    type Grid<T> = WfcGrid<T>;
+
+    fn num_cells(&self) -> usize {
+        self.width * self.height
+    }
+
+    fn position(
+        &self,
+        cell: usize,
+    ) -> Position {
+        p!(cell % self.width, cell / self.width)
+    }
+
+    fn cell(
+        &self,
+        position: Position,
+    ) -> Option<usize> {
+        grid_cell(self.width, self.height, position)
+    }
+
+    fn neighbours(
+        &self,
+        cell: usize,
+    ) -> impl Iterator<Item = (HexDirection, usize)> {
+        let position = self.position(cell);
+
+        HexDirection::ALL.into_iter().filter_map(move |dir| {
+            self.cell(self.neighbour(position, dir))
+                .map(|neighbour| (dir, neighbour))
+        })
+    }
+
+    fn grid<T>(
+        &self,
+        tiles: Vec<T>,
+    ) -> WfcGrid<T> {
+        WfcGrid::new(self.width, self.height, tiles)
+    }
+
+    fn validate(&self) -> Result<(), WfcError<Position>> {
+        if self.wrap_y && !self.height.is_multiple_of(2) {
+            return Err(WfcError::InvalidTopology(format!(
+                "A hex grid which wraps on the y axis must have an even height, but the height is {}",
+                self.height
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// The position of a cell on a [VoxelGrid]. The x and y coordinates are the position in a layer like in a 2D
+/// grid, and z is the layer.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct VoxelPosition {
+    pub x: isize,
+    pub y: isize,
+    pub z: isize,
+}
+
+impl VoxelPosition {
+    pub fn new(
+        x: isize,
+        y: isize,
+        z: isize,
+    ) -> Self {
+        VoxelPosition { x, y, z }
+    }
+
+    /// Return the position of the neighbour in the given [VoxelDirection], which might be outside the grid.
+    pub fn neighbour(
+        self,
+        direction: VoxelDirection,
+    ) -> VoxelPosition {
+        let (dx, dy, dz) = direction.offset();
+        VoxelPosition::new(self.x + dx, self.y + dy, self.z + dz)
+    }
+}
+
+/// A three-dimensional grid of cubes, where every cell has up to six face neighbours in the [VoxelDirection]s.
+///
+/// The grid consists of `depth` layers of `width * height` cells, which are stacked on top of each other.
+/// The cells are indexed layer by layer, starting with z = 0, and every layer in row-major order.
+/// The result of a collapse is a [WfcVolume].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct VoxelGrid {
+    width: usize,
+    height: usize,
+    depth: usize,
+}
+
+impl VoxelGrid {
+    pub fn new(
+        width: usize,
+        height: usize,
+        depth: usize,
+    ) -> Self {
+        VoxelGrid {
+            width,
+            height,
+            depth,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
+impl Topology for VoxelGrid {
+    type Position = VoxelPosition;
+    type Direction = VoxelDirection;
+    type Grid<T> = WfcVolume<T>;
+
+    fn num_cells(&self) -> usize {
+        self.width * self.height * self.depth
+    }
+
+    fn position(
+        &self,
+        cell: usize,
+    ) -> VoxelPosition {
+        let layer_size = self.width * self.height;
+        let (z, cell) = (cell / layer_size, cell % layer_size);
+
+        VoxelPosition::new(
+            (cell % self.width) as isize,
+            (cell / self.width) as isize,
+            z as isize,
+        )
+    }
+
+    fn cell(
+        &self,
+        position: VoxelPosition,
+    ) -> Option<usize> {
+        if position.z < 0 || position.z >= self.depth as isize {
+            return None;
+        }
+
+        let layer = self.width * self.height * position.z as usize;
+        grid_cell(self.width, self.height, p!(position.x, position.y)).map(|cell| layer + cell)
+    }
+
+    fn neighbours(
+        &self,
+        cell: usize,
+    ) -> impl Iterator<Item = (VoxelDirection, usize)> {
+        let position = self.position(cell);
+
+        VoxelDirection::ALL.into_iter().filter_map(move |dir| {
+            self.cell(position.neighbour(dir))
+                .map(|neighbour| (dir, neighbour))
+        })
+    }
+
+    fn grid<T>(
+        &self,
+        tiles: Vec<T>,
+    ) -> WfcVolume<T> {
+        WfcVolume::new(self.width, self.height, self.depth, tiles)
+    }
+}
+
+/// An arbitrary graph of cells, like the rooms of a building or the regions of a map, where the neighbours
+/// of every cell are given explicitly by labelled edges.
+///
+/// The position of a cell is simply its index. The directions can be any [NeighbourDirection], for example
+/// [Direction] to connect rooms by the wall they share, or [crate::direction::Undirected] if the edges have
+/// no direction. The result of a collapse is a [Vec] with the tile of every cell.
+///
+/// A cell can have several neighbours in the same direction. Such a graph is propagated by checking the
+/// constraints of the changed cells, which is slower than the AC-4 propagation of the other topologies.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Graph<D> {
+    /// The neighbours of every cell, alongside the direction they lie in
+    neighbours: Vec<Vec<(D, usize)>>,
+    /// Tells if every cell has at most one neighbour in every direction
+    unique_directions: bool,
+}
+
+impl<D: NeighbourDirection> Graph<D> {
+    /// Create a graph with the given amount of cells, which have no neighbours yet.
+    pub fn new(num_cells: usize) -> Self {
+        Graph {
+            neighbours: vec![Vec::new(); num_cells],
+            unique_directions: true,
+        }
+    }
+
+    /// Connect the given cells, so `to` becomes the neighbour of `from` in the given direction
+    /// and `from` becomes the neighbour of `to` in the opposite direction.
+    ///
+    /// Panics if one of the cells does not exist.
+    pub fn with_edge(
+        mut self,
+        from: usize,
+        direction: D,
+        to: usize,
+    ) -> Self {
+        self.add_edge(from, direction, to);
+        self
+    }
+
+    /// Connect the given cells like [Graph::with_edge].
+    pub fn add_edge(
+        &mut self,
+        from: usize,
+        direction: D,
+        to: usize,
+    ) {
+        self.add_neighbour(from, direction, to);
+        self.add_neighbour(to, direction.opposite(), from);
+    }
+
+    fn add_neighbour(
+        &mut self,
+        cell: usize,
+        direction: D,
+        neighbour: usize,
+    ) {
+        assert!(
+            neighbour < self.neighbours.len(),
+            "The cell {neighbour} should exist in the graph"
+        );
+
+        let neighbours = self
+            .neighbours
+            .get_mut(cell)
+            .unwrap_or_else(|| panic!("The cell {cell} should exist in the graph"));
+
+        if neighbours.iter().any(|(dir, _)| *dir == direction) {
+            self.unique_directions = false;
+        }
+
+        neighbours.push((direction, neighbour));
+    }
+}
+
+impl<D: NeighbourDirection> Topology for Graph<D> {
+    type Position = usize;
+    type Direction = D;
+    type Grid<T> = Vec<T>;
+
+    fn num_cells(&self) -> usize {
+        self.neighbours.len()
+    }
+
+    fn position(
+        &self,
+        cell: usize,
+    ) -> usize {
+        cell
+    }
+
+    fn cell(
+        &self,
+        position: usize,
+    ) -> Option<usize> {
+        (position < self.neighbours.len()).then_some(position)
+    }
+
+    fn neighbours(
+        &self,
+        cell: usize,
+    ) -> impl Iterator<Item = (D, usize)> {
+        self.neighbours[cell].iter().copied()
+    }
+
+    fn grid<T>(
+        &self,
+        tiles: Vec<T>,
+    ) -> Vec<T> {
+        tiles
+    }
+
+    fn unique_directions(&self) -> bool {
+        self.unique_directions
+    }
+}
+
+/// A [Topology] with holes, which only contains the cells of another topology which are included by a mask.
+/// This allows boards of any shape, like the outline of an island or the footprint of a building.
+///
+/// The excluded cells don't exist, so their neighbours treat them as absent. The cells keep the positions and
+/// directions of the inner topology, and the result of a collapse is the [Topology::Grid] of the inner
+/// topology, which holds None for every excluded cell.
+#[derive(Clone, Debug)]
+pub struct Masked<G: Topology> {
+    topology: G,
+    /// The index in this topology of every cell of the inner topology, or None if it is excluded
+    cells: Vec<Option<usize>>,
+    /// The index in the inner topology of every cell of this topology
+    inner_cells: Vec<usize>,
+}
+
+impl<G: Topology> Masked<G> {
+    /// Create the masked version of the given topology, which only includes the positions for which the
+    /// given mask returns true.
+    pub fn new(
+        topology: G,
+        included: impl Fn(G::Position) -> bool,
+    ) -> Self {
+        let mut cells = Vec::with_capacity(topology.num_cells());
+        let mut inner_cells = Vec::new();
+
+        for inner_cell in 0..topology.num_cells() {
+            if included(topology.position(inner_cell)) {
+                cells.push(Some(inner_cells.len()));
+                inner_cells.push(inner_cell);
+            } else {
+                cells.push(None);
+            }
+        }
+
+        Masked {
+            topology,
+            cells,
+            inner_cells,
+        }
+    }
+
+    /// Return the topology without the mask.
+    pub fn inner(&self) -> &G {
+        &self.topology
+    }
+}
+
+impl<G: Topology> Topology for Masked<G> {
+    type Position = G::Position;
+    type Direction = G::Direction;
+    type Grid<T> = G::Grid<Option<T>>;
+
+    fn num_cells(&self) -> usize {
+        self.inner_cells.len()
+    }
+
+    fn position(
+        &self,
+        cell: usize,
+    ) -> G::Position {
+        self.topology.position(self.inner_cells[cell])
+    }
+
+    fn cell(
+        &self,
+        position: G::Position,
+    ) -> Option<usize> {
+        self.topology
+            .cell(position)
+            .and_then(|inner_cell| self.cells[inner_cell])
+    }
+
+    fn neighbours(
+        &self,
+        cell: usize,
+    ) -> impl Iterator<Item = (G::Direction, usize)> {
+        self.topology
+            .neighbours(self.inner_cells[cell])
+            .filter_map(|(dir, inner_cell)| {
+                self.cells[inner_cell].map(|neighbour| (dir, neighbour))
+            })
+    }
+
+    fn grid<T>(
+        &self,
+        tiles: Vec<T>,
+    ) -> G::Grid<Option<T>> {
+        // the included cells keep the order of the inner topology
+        let mut tiles = tiles.into_iter();
+        let tiles = self
+            .cells
+            .iter()
+            .map(|cell| cell.and_then(|_| tiles.next()))
+            .collect();
+
+        self.topology.grid(tiles)
+    }
+
+    fn unique_directions(&self) -> bool {
+        // excluded cells only remove neighbours
+        self.topology.unique_directions()
+    }
+
+    fn validate(&self) -> Result<(), WfcError<G::Position>> {
+        self.topology.validate()
+    }
+}
+
+/// Wrap the given position around the edges of a grid with the given size and wrapping of each axis.
+fn wrap(
+    (width, wrap_x): (usize, bool),
+    (height, wrap_y): (usize, bool),
+    position: Position,
+) -> Position {
+    let x = match wrap_x {
+        true => position.x.rem_euclid(width as isize),
+        false => position.x,
+    };
+    let y = match wrap_y {
+        true => position.y.rem_euclid(height as isize),
+        false => position.y,
+    };
+
+    p!(x, y)
+}
+
+/// Return the row-major index of the given position in a grid with the given dimensions,
+/// or None if it is out of bounds.
+fn grid_cell(
+    width: usize,
+    height: usize,
+    position: Position,
+) -> Option<usize> {
+    if position.x < 0
+        || position.y < 0
+        || position.x >= width as isize
+        || position.y >= height as isize
+    {
+        return None;
+    }
+
+    Some(width * position.y as usize + position.x as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        direction::{Direction, HexDirection, VoxelDirection},
+        topology::{Graph, HexGrid, Masked, SquareGrid, Topology, VoxelGrid, VoxelPosition},
+    };
+    use pad::p;
+
+    #[test]
+    fn hex_neighbours_are_mutual() {
+        let grid = HexGrid::new(4, 4);
+
+        for cell in 0..grid.num_cells() {
+            for (dir, neighbour) in grid.neighbours(cell) {
+                assert!(
+                    grid.neighbours(neighbour)
+                        .any(|(d, n)| d == dir.opposite() && n == cell)
+                );
+            }
+        }
+
+        // an inner hexagon has six neighbours
+        assert_eq!(grid.neighbours(grid.cell(p!(1, 1)).unwrap()).count(), 6);
+        assert_eq!(grid.neighbour(p!(1, 1), HexDirection::UpLeft), p!(1, 0));
+        assert_eq!(grid.neighbour(p!(1, 2), HexDirection::UpLeft), p!(0, 1));
+
+        let axial = HexGrid::to_axial(p!(2, 3));
+        assert_eq!(HexGrid::from_axial(axial.0, axial.1), p!(2, 3));
+    }
+
+    #[test]
+    fn wrapping_connects_opposite_edges() {
+        let grid = SquareGrid::new(3, 2).with_wrapping(true, false);
+        let neighbours = grid
+            .neighbours(0)
+            .map(|(_, n)| grid.position(n))
+            .collect::<Vec<_>>();
+
+        // the left neighbour of the upper left corner is on the right edge, but nothing is above it
+        assert_eq!(neighbours, vec![p!(0, 1), p!(2, 0), p!(1, 0)]);
+
+        let hex_grid = HexGrid::new(4, 4).with_wrapping(true, true);
+
+        for cell in 0..hex_grid.num_cells() {
+            assert_eq!(hex_grid.neighbours(cell).count(), 6);
+
+            for (dir, neighbour) in hex_grid.neighbours(cell) {
+                assert!(
+                    hex_grid
+                        .neighbours(neighbour)
+                        .any(|(d, n)| d == dir.opposite() && n == cell)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn voxel_neighbours_span_layers() {
+        let grid = VoxelGrid::new(3, 2, 2);
+        let position = VoxelPosition::new(1, 0, 1);
+        let cell = grid.cell(position).unwrap();
+
+        assert_eq!(grid.position(cell), position);
+
+        let neighbours = grid
+            .neighbours(cell)
+            .map(|(dir, n)| (dir, grid.position(n)))
+            .collect::<Vec<_>>();
+
+        // the cell is in the upper layer and the upper row, so it has no neighbour above and up
+        assert_eq!(
+            neighbours,
+            vec![
+                (VoxelDirection::Down, VoxelPosition::new(1, 1, 1)),
+                (VoxelDirection::Left, VoxelPosition::new(0, 0, 1)),
+                (VoxelDirection::Right, VoxelPosition::new(2, 0, 1)),
+                (VoxelDirection::Below, VoxelPosition::new(1, 0, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn graph_edges_connect_both_cells() {
+        // a hallway with a room on each side and one at its end
+        let graph = Graph::new(4)
+            .with_edge(0, Direction::Left, 1)
+            .with_edge(0, Direction::Right, 2)
+            .with_edge(0, Direction::Up, 3);
+
+        assert_eq!(graph.num_cells(), 4);
+        assert_eq!(graph.neighbours(0).count(), 3);
+        assert_eq!(
+            graph.neighbours(3).collect::<Vec<_>>(),
+            vec![(Direction::Down, 0)]
+        );
+        assert_eq!(graph.cell(4), None);
+    }
+
+    #[test]
+    fn masked_cells_are_absent() {
+        // a 3x3 grid with a hole in the middle
+        let grid = Masked::new(SquareGrid::new(3, 3), |pos| pos != p!(1, 1));
+
+        assert_eq!(grid.num_cells(), 8);
+        assert_eq!(grid.cell(p!(1, 1)), None);
+        assert_eq!(grid.position(grid.cell(p!(2, 1)).unwrap()), p!(2, 1));
+
+        // the upper center cell lost its neighbour below
+        let neighbours = grid
+            .neighbours(grid.cell(p!(1, 0)).unwrap())
+            .map(|(_, n)| grid.position(n))
+            .collect::<Vec<_>>();
+        assert_eq!(neighbours, vec![p!(0, 0), p!(2, 0)]);
+
+        let result = grid.grid((0..8).collect());
+        assert_eq!(result.get(p!(1, 1)), Some(&None));
+        assert_eq!(result.get(p!(2, 2)), Some(&Some(7)));
+    }
+}